@@ -1,10 +1,20 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use bevy::prelude::{App, Component, Entity, Event, EventReader, Handle, Image, in_state, info, IntoSystemConfigs, Plugin, Query, Res, Resource, TextureAtlas, TextureAtlasLayout, Time, Timer, TimerMode, Update};
-use bevy::utils::info;
+use bevy::prelude::{error, App, AppExit, Asset, AssetApp, Assets, AssetServer, Bundle, Commands, Component, Deref, Entity, Event, EventReader, EventWriter, Handle, Image, in_state, info, IntoSystemConfigs, NextState, OnEnter, Plugin, Query, Res, ResMut, Resource, Sprite, TextureAtlas, TextureAtlasLayout, Time, Timer, TimerMode, TypePath, Update, Vec2, warn};
+use bevy::asset::{LoadState, RecursiveDependencyLoadState};
+use bevy::ecs::query::QueryData;
+use serde::Deserialize;
+
+use crate::animation::aseprite::AsepriteLoader;
+use crate::animation::loader::AnimationLibraryLoader;
+use crate::animation::state_machine::{AnimationFinishedEvent, AnimationStateMachine};
+use crate::AppState;
 use crate::game::game::GameState;
-use crate::game::movement::movement::Direction;
+use crate::game::movement::movement::{CharacterVelocity, Direction, MovementProfile};
+
+pub const FOOTSTEP_MARKER: &str = "footstep";
+pub const HIT_MARKER: &str = "hit";
 
 pub struct PepaAnimationPlugin;
 
@@ -12,37 +22,164 @@ impl Plugin for PepaAnimationPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<ClipChangeEvent>()
-            .init_resource::<AnimationLibrary>()
+            .add_event::<AnimationFinishedEvent>()
+            .add_event::<AnimationMarkerEvent>()
+            .init_asset::<AnimationLibrary>()
+            .init_asset_loader::<AnimationLibraryLoader>()
+            .init_asset_loader::<AsepriteLoader>()
+            .init_resource::<AnimationLibraries>()
+            .add_systems(OnEnter(AppState::Loading), load_animation_libraries)
+            .add_systems(
+                Update,
+                check_animation_libraries_loading.run_if(in_state(AppState::Loading)),
+            )
             .add_systems(
                 Update,
                 (
+                    scale_animation_speed,
                     animate_clip,
+                    finish_one_shot_clips,
                     change_animation_clip
-                ).run_if(in_state(GameState::Running)),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Running)),
             );
     }
 }
 
-#[derive(Default, Eq, Hash, PartialEq, Copy, Clone, Debug)]
+#[derive(Default, Eq, Hash, PartialEq, Copy, Clone, Debug, Deserialize)]
 pub enum AnimationState {
     #[default]
     Idle,
     Walk,
     Run,
-    Attack
+    Attack,
+    Attack2,
+    Attack3,
+    Dash,
+    Shield,
+    Hurt,
+    Dead,
+}
+
+impl AnimationState {
+    pub const STATES: [Self; 10] = [
+        AnimationState::Idle,
+        AnimationState::Walk,
+        AnimationState::Run,
+        AnimationState::Attack,
+        AnimationState::Attack2,
+        AnimationState::Attack3,
+        AnimationState::Dash,
+        AnimationState::Shield,
+        AnimationState::Hurt,
+        AnimationState::Dead,
+    ];
+
+    pub fn priority(&self) -> u8 {
+        match self {
+            AnimationState::Idle => 0,
+            AnimationState::Walk => 1,
+            AnimationState::Run => 1,
+            AnimationState::Attack => 2,
+            AnimationState::Attack2 => 2,
+            AnimationState::Attack3 => 2,
+            AnimationState::Dash => 3,
+            AnimationState::Shield => 2,
+            AnimationState::Hurt => 4,
+            AnimationState::Dead => 5,
+        }
+    }
+
+    pub fn is_one_shot(&self) -> bool {
+        self.is_attack() || matches!(self, AnimationState::Dash | AnimationState::Hurt | AnimationState::Dead)
+    }
+
+    pub fn is_attack(&self) -> bool {
+        matches!(self, AnimationState::Attack | AnimationState::Attack2 | AnimationState::Attack3)
+    }
+
+    pub fn is_locomotion(&self) -> bool {
+        matches!(self, AnimationState::Walk | AnimationState::Run)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        *self == AnimationState::Dead
+    }
+}
+
+#[derive(Eq, Hash, PartialEq, Copy, Clone, Debug)]
+pub enum AnimatedCharacter {
+    RawPlayer,
+    Fighter,
+    Samurai,
+    Shinobi,
+}
+
+impl AnimatedCharacter {
+    pub const CHARACTERS: [Self; 4] = [
+        AnimatedCharacter::RawPlayer,
+        AnimatedCharacter::Fighter,
+        AnimatedCharacter::Samurai,
+        AnimatedCharacter::Shinobi,
+    ];
+
+    pub fn asset_path(&self) -> &'static str {
+        match self {
+            AnimatedCharacter::RawPlayer => "animations/raw_player.anim.ron",
+            AnimatedCharacter::Fighter => "sprites/characters/fighter/fighter.aseprite",
+            AnimatedCharacter::Samurai => "animations/samurai.anim.ron",
+            AnimatedCharacter::Shinobi => "animations/shinobi.anim.ron",
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlaybackMode {
+    #[default]
+    Loop,
+    Once,
+    PingPong,
+    Reverse,
 }
 
-#[derive(Component, Debug, Clone, Eq, PartialEq, Default)]
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FrameMarker {
+    pub frame: usize,
+    pub name: String,
+}
+
+#[derive(Component, Debug, Clone, PartialEq)]
 pub struct AnimationClip {
     pub indices: AnimationIndices,
     pub timer: Timer,
+    pub frame_durations: Vec<Duration>,
+    pub markers: Vec<FrameMarker>,
+    pub mode: PlaybackMode,
+    pub speed: f32,
+    pub reversed: bool,
+    pub started: bool,
+    pub finished: bool,
+}
+
+impl Default for AnimationClip {
+    fn default() -> Self {
+        Self::new_with_timer(AnimationIndices::default(), Timer::default())
+    }
 }
 
 impl AnimationClip {
-    pub fn new(indices: AnimationIndices, timer_mills: u64, timer_mode: TimerMode) -> Self {
+    pub fn new(indices: AnimationIndices, timer_mills: u64, mode: PlaybackMode) -> Self {
         Self {
             indices,
-            timer: Timer::from_seconds(Duration::from_millis(timer_mills).as_secs_f32(), timer_mode),
+            timer: Timer::from_seconds(Duration::from_millis(timer_mills).as_secs_f32(), TimerMode::Repeating),
+            frame_durations: Vec::new(),
+            markers: Vec::new(),
+            mode,
+            speed: 1.0,
+            reversed: false,
+            started: false,
+            finished: false,
         }
     }
 
@@ -50,7 +187,93 @@ impl AnimationClip {
         Self {
             indices,
             timer,
+            frame_durations: Vec::new(),
+            markers: Vec::new(),
+            mode: PlaybackMode::Loop,
+            speed: 1.0,
+            reversed: false,
+            started: false,
+            finished: false,
+        }
+    }
+
+    pub fn from_resource(resource: &AnimationClipResource) -> Self {
+        let mut clip = Self {
+            indices: resource.indices.clone(),
+            timer: resource.timer.clone(),
+            frame_durations: resource.frame_durations.clone(),
+            markers: resource.markers.clone(),
+            mode: resource.mode,
+            speed: 1.0,
+            reversed: false,
+            started: false,
+            finished: false,
+        };
+
+        if let Some(duration) = clip.frame_duration(clip.start_index()) {
+            clip.timer.set_duration(duration);
+        }
+
+        clip
+    }
+
+    pub fn frame_duration(&self, index: usize) -> Option<Duration> {
+        let frame = index.checked_sub(self.indices.first)?;
+        self.frame_durations.get(frame).copied()
+    }
+
+    pub fn start_index(&self) -> usize {
+        match self.mode {
+            PlaybackMode::Reverse => self.indices.last,
+            _ => self.indices.first,
+        }
+    }
+
+    pub fn next_index(&mut self, index: usize) -> Option<usize> {
+        let (first, last) = (self.indices.first, self.indices.last);
+
+        if index < first || index > last {
+            return Some(self.start_index());
         }
+
+        if first == last {
+            self.finished = true;
+            return (self.mode != PlaybackMode::Once).then_some(first);
+        }
+
+        match self.mode {
+            PlaybackMode::Loop if index < last => Some(index + 1),
+            PlaybackMode::Loop => {
+                self.finished = true;
+                Some(first)
+            }
+            PlaybackMode::Once if index < last => Some(index + 1),
+            PlaybackMode::Once => {
+                self.finished = true;
+                None
+            }
+            PlaybackMode::Reverse if index > first => Some(index - 1),
+            PlaybackMode::Reverse => {
+                self.finished = true;
+                Some(last)
+            }
+            PlaybackMode::PingPong if !self.reversed && index < last => Some(index + 1),
+            PlaybackMode::PingPong if !self.reversed => {
+                self.reversed = true;
+                Some(index - 1)
+            }
+            PlaybackMode::PingPong if index > first => Some(index - 1),
+            PlaybackMode::PingPong => {
+                self.reversed = false;
+                self.finished = true;
+                Some(index + 1)
+            }
+        }
+    }
+
+    pub fn markers_at(&self, index: usize) -> impl Iterator<Item = &FrameMarker> {
+        let frame = index.checked_sub(self.indices.first);
+        self.markers.iter().filter(move |marker| Some(marker.frame) == frame)
     }
 }
 
@@ -58,15 +281,34 @@ impl AnimationClip {
 pub struct AnimationClipResource {
     pub indices: AnimationIndices,
     pub timer: Timer,
+    pub frame_durations: Vec<Duration>,
+    pub markers: Vec<FrameMarker>,
+    pub mode: PlaybackMode,
 }
 
 impl AnimationClipResource {
-    pub fn new(indices: AnimationIndices, timer_mills: u64, timer_mode: TimerMode) -> Self {
+    pub fn new(indices: AnimationIndices, timer_mills: u64, mode: PlaybackMode) -> Self {
         Self {
             indices,
-            timer: Timer::from_seconds(Duration::from_millis(timer_mills).as_secs_f32(), timer_mode),
+            timer: Timer::from_seconds(Duration::from_millis(timer_mills).as_secs_f32(), TimerMode::Repeating),
+            frame_durations: Vec::new(),
+            markers: Vec::new(),
+            mode,
         }
     }
+
+    pub fn with_markers(mut self, markers: Vec<FrameMarker>) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    pub fn with_frame_durations(mut self, frame_durations: Vec<Duration>) -> Self {
+        if let Some(first) = frame_durations.first() {
+            self.timer.set_duration(*first);
+        }
+        self.frame_durations = frame_durations;
+        self
+    }
 }
 
 #[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
@@ -81,6 +323,25 @@ impl AnimationIndices {
     }
 }
 
+#[derive(Event, Debug, Clone)]
+pub struct AnimationMarkerEvent {
+    pub entity: Entity,
+    pub state: AnimationState,
+    pub direction: Direction,
+    pub marker: String,
+}
+
+impl AnimationMarkerEvent {
+    pub fn new(entity: &Entity, state: AnimationState, direction: Direction, marker: &str) -> Self {
+        Self {
+            entity: *entity,
+            state,
+            direction,
+            marker: marker.to_string(),
+        }
+    }
+}
+
 #[derive(Event, Debug)]
 pub struct ClipChangeEvent {
     pub entity: Entity,
@@ -96,6 +357,10 @@ impl ClipChangeEvent {
             new_direction,
         }
     }
+
+    pub fn towards(entity: &Entity, new_state: AnimationState, vector: Vec2) -> Self {
+        Self::new(entity, new_state, Direction::from_vec(vector))
+    }
 }
 
 #[derive(Resource, Default, Debug)]
@@ -113,56 +378,304 @@ impl AnimationResource {
     }
 }
 
-#[derive(Resource, Default, Debug)]
+#[derive(Asset, TypePath, Default, Debug)]
 pub struct AnimationLibrary {
     pub clips: HashMap<(AnimationState, Direction), (AnimationClipResource, AnimationResource)>,
+    pub mirrored: bool,
+}
+
+impl AnimationLibrary {
+    pub fn get_clip(&self, state: AnimationState, direction: Direction) -> Option<&(AnimationClipResource, AnimationResource)> {
+        self.clips.get(&(state, direction))
+    }
+
+    pub fn resolve_clip(&self, state: AnimationState, direction: Direction) -> Option<(&AnimationClipResource, &AnimationResource, Option<bool>)> {
+        let keep_flip = self.mirrored && !direction.has_x_axis();
+
+        if let Some((clip, resource)) = self.get_clip(state, direction) {
+            return Some((clip, resource, (!keep_flip).then_some(false)));
+        }
+
+        if !self.mirrored {
+            let (clip, resource) = self.nearest_clip(state, direction)?;
+            return Some((clip, resource, Some(false)));
+        }
+
+        let flip_x = if keep_flip { None } else { Some(direction.is_neg_x_axes()) };
+
+        self.get_clip(state, direction.right_facing())
+            .or_else(|| self.nearest_clip(state, direction.right_facing()))
+            .map(|(clip, resource)| (clip, resource, flip_x))
+    }
+
+    pub fn idle_clip(&self, direction: Direction) -> Option<(&AnimationClipResource, &AnimationResource)> {
+        if let Some((clip, resource, _)) = self.resolve_clip(AnimationState::Idle, direction) {
+            return Some((clip, resource));
+        }
+
+        std::iter::once(Direction::Zero)
+            .chain(Direction::DIRECTIONS)
+            .find_map(|direction| self.get_clip(AnimationState::Idle, direction))
+            .map(|(clip, resource)| (clip, resource))
+    }
+
+    fn nearest_clip(&self, state: AnimationState, direction: Direction) -> Option<&(AnimationClipResource, AnimationResource)> {
+        let target = direction.to_vec2();
+        if target == Vec2::ZERO {
+            return self.get_clip(state, Direction::Zero);
+        }
+
+        Direction::DIRECTIONS
+            .iter()
+            .filter(|candidate| candidate.to_vec2().dot(target) >= 0.0)
+            .filter_map(|candidate| self.get_clip(state, *candidate).map(|clip| (candidate.to_vec2().dot(target), clip)))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, clip)| clip)
+            .or_else(|| self.get_clip(state, Direction::Zero))
+    }
+}
+
+#[derive(Component, Default, Debug, Clone, Deref)]
+pub struct AnimationLibraryHandle(pub Handle<AnimationLibrary>);
+
+#[derive(Resource, Default, Debug)]
+pub struct AnimationLibraries {
+    pub libraries: HashMap<AnimatedCharacter, Handle<AnimationLibrary>>,
+}
+
+impl AnimationLibraries {
+    pub fn get(&self, character: AnimatedCharacter) -> Handle<AnimationLibrary> {
+        self.libraries.get(&character).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Bundle, Default)]
+pub struct AnimationBundle {
+    pub library: AnimationLibraryHandle,
+    pub state_machine: AnimationStateMachine,
+    pub clip: AnimationClip,
+    pub atlas: TextureAtlas,
+}
+
+impl AnimationBundle {
+    pub fn new(library: Handle<AnimationLibrary>, clip: &AnimationClipResource, resource: &AnimationResource) -> Self {
+        let clip = AnimationClip::from_resource(clip);
+        let index = clip.start_index();
+
+        Self {
+            library: AnimationLibraryHandle(library),
+            state_machine: AnimationStateMachine::default(),
+            clip,
+            atlas: TextureAtlas {
+                layout: resource.atlas_layout.clone(),
+                index,
+            },
+        }
+    }
+
+    pub fn with_state_machine(mut self, state_machine: AnimationStateMachine) -> Self {
+        self.state_machine = state_machine;
+        self
+    }
+}
+
+pub fn load_animation_libraries(mut commands: Commands, asset_server: Res<AssetServer>) {
+    info!("Loading animation libraries");
+
+    commands.insert_resource(AnimationLibraries {
+        libraries: AnimatedCharacter::CHARACTERS
+            .iter()
+            .map(|character| (*character, asset_server.load(character.asset_path())))
+            .collect(),
+    });
+}
+
+pub fn check_animation_libraries_loading(
+    asset_server: Res<AssetServer>,
+    animation_libraries: Res<AnimationLibraries>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit_writer: EventWriter<AppExit>,
+) {
+    for (character, handle) in animation_libraries.libraries.iter() {
+        if let LoadState::Failed(load_error) = asset_server.load_state(handle.id()) {
+            error!("Could not load animation library for {:?}: {}", character, load_error);
+            exit_writer.send(AppExit::error());
+            return;
+        }
+
+        if asset_server.recursive_dependency_load_state(handle.id()) == RecursiveDependencyLoadState::Failed {
+            error!("Could not load textures of animation library for {:?}", character);
+            exit_writer.send(AppExit::error());
+            return;
+        }
+    }
+
+    let is_loaded = animation_libraries
+        .libraries
+        .values()
+        .all(|handle| asset_server.is_loaded_with_dependencies(handle.id()));
+
+    if is_loaded {
+        info!("Assets has been loaded");
+        next_state.set(AppState::MainMenu)
+    }
 }
 
 pub fn animate_clip(
     time: Res<Time>,
-    mut query: Query<(&mut AnimationClip, &mut TextureAtlas)>,
+    mut query: Query<(Entity, &mut AnimationClip, &mut TextureAtlas, Option<&AnimationStateMachine>)>,
+    mut marker_writer: EventWriter<AnimationMarkerEvent>,
 ) {
-    for (mut animation_clip, mut atlas) in query.iter_mut() {
-        animation_clip.timer.tick(time.delta());
+    for (entity, mut animation_clip, mut atlas, state_machine) in query.iter_mut() {
+        let mut entered_frame = None;
+        if !animation_clip.started {
+            animation_clip.started = true;
+            entered_frame = Some(atlas.index);
+        }
+
+        let delta = time.delta().mul_f32(animation_clip.speed.max(0.0));
+        animation_clip.timer.tick(delta);
         info!("Timer tick atlas index: {:?}", &atlas.index);
 
         if animation_clip.timer.just_finished() {
-            atlas.index = match atlas.index {
-                idx if idx < animation_clip.indices.first => animation_clip.indices.first,
-                idx if idx < animation_clip.indices.last => idx + 1,
-                _ => animation_clip.indices.first
-            };
+            if let Some(index) = animation_clip.next_index(atlas.index) {
+                atlas.index = index;
+                entered_frame = Some(index);
+            }
+
+            if let Some(duration) = animation_clip.frame_duration(atlas.index) {
+                animation_clip.timer.set_duration(duration);
+            }
+        }
+
+        let Some(frame) = entered_frame else {
+            continue;
+        };
+
+        let (state, direction) = state_machine
+            .map(|state_machine| (state_machine.state, state_machine.direction))
+            .unwrap_or_default();
+
+        for marker in animation_clip.markers_at(frame) {
+            info!("Animation marker: {:?} for entity: {:?}", marker.name, entity);
+            marker_writer.send(AnimationMarkerEvent::new(&entity, state, direction, &marker.name));
         }
     }
 }
 
+pub fn scale_animation_speed(
+    mut query: Query<(&AnimationStateMachine, &mut AnimationClip, &CharacterVelocity, &MovementProfile)>,
+) {
+    for (state_machine, mut animation_clip, velocity, profile) in query.iter_mut() {
+        if state_machine.state.is_locomotion() && profile.max_speed > 0.0 {
+            animation_clip.speed = velocity.length() / profile.max_speed;
+        }
+    }
+}
+
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct ClipPlayback {
+    entity: Entity,
+    library: &'static AnimationLibraryHandle,
+    state_machine: &'static mut AnimationStateMachine,
+    clip: &'static mut AnimationClip,
+    atlas: &'static mut TextureAtlas,
+    texture: &'static mut Handle<Image>,
+    sprite: &'static mut Sprite,
+}
+
+pub fn finish_one_shot_clips(
+    mut query: Query<ClipPlayback>,
+    mut finished_writer: EventWriter<AnimationFinishedEvent>,
+    libraries: Res<Assets<AnimationLibrary>>,
+) {
+    for mut playback in query.iter_mut() {
+        if !playback.state_machine.state.is_one_shot() || !playback.clip.finished {
+            continue;
+        }
+
+        let animation_library = libraries.get(&playback.library.0);
+        let can_enter = |state, direction| {
+            animation_library.is_none_or(|library| library.resolve_clip(state, direction).is_some())
+        };
+        let Some((state, direction)) = playback.state_machine.finish(can_enter) else {
+            continue;
+        };
+
+        info!("One-shot clip finished for entity: {:?}, state: {:?}", playback.entity, state);
+        finished_writer.send(AnimationFinishedEvent::new(&playback.entity, state, direction));
+
+        if playback.state_machine.is_held() {
+            continue;
+        }
+
+        let Some(animation_library) = animation_library else {
+            continue;
+        };
+
+        play_clip(animation_library, &mut playback);
+    }
+}
+
 pub fn change_animation_clip(
-    mut query: Query<(&mut AnimationClip, &mut TextureAtlas, &mut Handle<Image>)>,
+    mut query: Query<ClipPlayback>,
     mut event_reader: EventReader<ClipChangeEvent>,
-    animation_library: Res<AnimationLibrary>,
+    mut finished_writer: EventWriter<AnimationFinishedEvent>,
+    libraries: Res<Assets<AnimationLibrary>>,
 ) {
     for event in event_reader.read() {
         info!("Change animation clip event: {:?}", event);
-        let Ok((mut animation_clip, mut texture_atlas, mut texture)) = query.get_mut(event.entity) else {
+        let Ok(mut playback) = query.get_mut(event.entity) else {
             continue;
         };
 
-        let Some((new_clip, resource)) = animation_library.clips.get(&(event.new_state, event.new_direction)) else {
-            panic!("No clip found for state: {:?} and direction: {:?}", event.new_state, event.new_direction);
+        let Some(animation_library) = libraries.get(&playback.library.0) else {
+            continue;
         };
 
-        if !animation_clip.timer.just_finished() {
+        let (state, direction) = (event.new_state, event.new_direction);
+        let has_clip = animation_library.resolve_clip(state, direction).is_some();
+        if !has_clip && !state.is_terminal() {
+            warn!("No clip found for entity: {:?}, state: {:?} and direction: {:?}", playback.entity, state, direction);
             continue;
         }
 
-        info!("Changing clip to: {:?} with resource: {:?}", &new_clip, &resource);
+        if !playback.state_machine.request(state, direction) {
+            continue;
+        }
 
-        animation_clip.indices = new_clip.indices.clone();
-        animation_clip.timer = new_clip.timer.clone();
-        *texture = resource.texture.clone();
-        *texture_atlas = TextureAtlas {
-            layout: resource.atlas_layout.clone(),
-            index: texture_atlas.index,
-        };
+        // A terminal state can't be refused, so without a clip it finishes straight away.
+        if !has_clip {
+            warn!("No clip found for entity: {:?}, state: {:?}, finishing it now", playback.entity, state);
+            if let Some((state, direction)) = playback.state_machine.finish(|_, _| true) {
+                finished_writer.send(AnimationFinishedEvent::new(&playback.entity, state, direction));
+            }
+            continue;
+        }
+
+        play_clip(animation_library, &mut playback);
+    }
+}
+
+fn play_clip(animation_library: &AnimationLibrary, playback: &mut ClipPlaybackItem) {
+    let (state, direction) = (playback.state_machine.state, playback.state_machine.direction);
+
+    let Some((new_clip, resource, flip_x)) = animation_library.resolve_clip(state, direction) else {
+        warn!("No clip found for entity: {:?}, state: {:?} and direction: {:?}", playback.entity, state, direction);
+        return;
+    };
+
+    info!("Changing clip to: {:?} with resource: {:?}", &new_clip, &resource);
+
+    *playback.clip = AnimationClip::from_resource(new_clip);
+    *playback.texture = resource.texture.clone();
+    *playback.atlas = TextureAtlas {
+        layout: resource.atlas_layout.clone(),
+        index: playback.clip.start_index(),
+    };
+
+    if let Some(flip_x) = flip_x {
+        playback.sprite.flip_x = flip_x;
     }
 }
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use asefile::{AnimationDirection, AsepriteFile, AsepriteParseError};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::{Image, TextureAtlasLayout, UVec2};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use thiserror::Error;
+
+use crate::animation::animation::{
+    AnimationClipResource, AnimationIndices, AnimationLibrary, AnimationResource, AnimationState,
+    FrameMarker, PlaybackMode,
+};
+use crate::game::movement::movement::Direction;
+
+#[derive(Default)]
+pub struct AsepriteLoader;
+
+#[derive(Debug, Error)]
+pub enum AsepriteLoaderError {
+    #[error("could not read aseprite file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse aseprite file: {0}")]
+    Parse(#[from] AsepriteParseError),
+    #[error("aseprite file has no tags to build clips from")]
+    NoTags,
+    #[error("tag '{tag}' does not name an animation state and optional direction, e.g. 'Walk' or 'Walk_Left'")]
+    UnknownTag { tag: String },
+    #[error("tag '{tag}' is declared more than once")]
+    DuplicateTag { tag: String },
+    #[error("aseprite file has no Idle tag to spawn characters with")]
+    MissingIdleTag,
+    #[error("tag '{tag}' has invalid markers in its user data: {error}")]
+    InvalidMarkers { tag: String, error: ron::error::SpannedError },
+    #[error("tag '{tag}' has marker '{marker}' at frame {frame}, past its last frame")]
+    MarkerOutOfRange { tag: String, marker: String, frame: usize },
+}
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = AnimationLibrary;
+    type Settings = ();
+    type Error = AsepriteLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = AsepriteFile::read(bytes.as_slice())?;
+
+        if file.num_tags() == 0 {
+            return Err(AsepriteLoaderError::NoTags);
+        }
+
+        let mut keys = Vec::new();
+        for tag_id in 0..file.num_tags() {
+            let tag = file.tag(tag_id);
+            let key = parse_tag_name(tag.name())
+                .ok_or_else(|| AsepriteLoaderError::UnknownTag { tag: tag.name().to_string() })?;
+
+            if keys.contains(&key) {
+                return Err(AsepriteLoaderError::DuplicateTag { tag: tag.name().to_string() });
+            }
+            keys.push(key);
+        }
+
+        if !keys.iter().any(|(state, _)| *state == AnimationState::Idle) {
+            return Err(AsepriteLoaderError::MissingIdleTag);
+        }
+
+        let (frame_width, frame_height) = (file.width() as u32, file.height() as u32);
+        let columns = sheet_columns(file.num_frames());
+        let rows = file.num_frames().div_ceil(columns);
+
+        let texture = load_context.add_labeled_asset("texture".to_string(), build_sheet(&file, columns, rows));
+        let atlas_layout = load_context.add_labeled_asset(
+            "layout".to_string(),
+            TextureAtlasLayout::from_grid(UVec2::new(frame_width, frame_height), columns, rows, None, None),
+        );
+
+        let mut clips = HashMap::new();
+        for (tag_id, key) in keys.into_iter().enumerate() {
+            let tag = file.tag(tag_id as u32);
+            let frame_durations: Vec<Duration> = (tag.from_frame()..=tag.to_frame())
+                .map(|frame| Duration::from_millis(file.frame(frame).duration() as u64))
+                .collect();
+
+            let markers = parse_markers(tag.name(), tag.user_data().and_then(|data| data.text.as_deref()))?;
+            if let Some(marker) = markers.iter().find(|marker| tag.from_frame() as usize + marker.frame > tag.to_frame() as usize) {
+                return Err(AsepriteLoaderError::MarkerOutOfRange {
+                    tag: tag.name().to_string(),
+                    marker: marker.name.clone(),
+                    frame: marker.frame,
+                });
+            }
+
+            let clip = AnimationClipResource::new(
+                AnimationIndices::new(tag.from_frame() as usize, tag.to_frame() as usize),
+                file.frame(tag.from_frame()).duration() as u64,
+                playback_mode(tag.animation_direction(), tag.repeat().map(|repeat| repeat.get())),
+            )
+            .with_frame_durations(frame_durations)
+            .with_markers(markers);
+
+            clips.insert(key, (clip, AnimationResource::new(texture.clone(), atlas_layout.clone())));
+        }
+
+        // Sheets drawn facing right only are flipped for the left, like `mirrored` libraries in RON.
+        let mirrored = clips.keys().any(|(_, direction)| *direction == Direction::Right)
+            && !clips.keys().any(|(_, direction)| *direction == Direction::Left);
+
+        Ok(AnimationLibrary { clips, mirrored })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
+
+fn playback_mode(direction: AnimationDirection, repeat: Option<u32>) -> PlaybackMode {
+    match direction {
+        AnimationDirection::Forward if repeat == Some(1) => PlaybackMode::Once,
+        AnimationDirection::Forward => PlaybackMode::Loop,
+        AnimationDirection::Reverse => PlaybackMode::Reverse,
+        AnimationDirection::PingPong => PlaybackMode::PingPong,
+    }
+}
+
+fn parse_tag_name(name: &str) -> Option<(AnimationState, Direction)> {
+    let (state, direction) = match name.split_once('_') {
+        Some((state, direction)) => (state, Some(direction)),
+        None => (name, None),
+    };
+
+    let state = ron::de::from_str::<AnimationState>(state).ok()?;
+    let direction = match direction {
+        Some(direction) => ron::de::from_str::<Direction>(direction).ok()?,
+        None => Direction::Zero,
+    };
+
+    Some((state, direction))
+}
+
+fn parse_markers(tag: &str, text: Option<&str>) -> Result<Vec<FrameMarker>, AsepriteLoaderError> {
+    let Some(text) = text.filter(|text| !text.trim().is_empty()) else {
+        return Ok(Vec::new());
+    };
+
+    ron::de::from_str(text).map_err(|error| AsepriteLoaderError::InvalidMarkers { tag: tag.to_string(), error })
+}
+
+fn sheet_columns(frames: u32) -> u32 {
+    (frames as f32).sqrt().ceil().max(1.0) as u32
+}
+
+fn build_sheet(file: &AsepriteFile, columns: u32, rows: u32) -> Image {
+    let (frame_width, frame_height) = (file.width(), file.height());
+    let (columns, rows) = (columns as usize, rows as usize);
+    let row_bytes = frame_width * 4;
+    let sheet_row_bytes = row_bytes * columns;
+    let mut data = vec![0; sheet_row_bytes * frame_height * rows];
+
+    for frame in 0..file.num_frames() as usize {
+        let (column, sheet_row) = (frame % columns, frame / columns);
+        let image = file.frame(frame as u32).image();
+        for (row, pixels) in image.as_raw().chunks_exact(row_bytes).enumerate() {
+            let offset = (sheet_row * frame_height + row) * sheet_row_bytes + column * row_bytes;
+            data[offset..offset + row_bytes].copy_from_slice(pixels);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: (frame_width * columns) as u32,
+            height: (frame_height * rows) as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
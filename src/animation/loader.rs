@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::{TextureAtlasLayout, UVec2};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::animation::animation::{
+    AnimationClipResource, AnimationIndices, AnimationLibrary, AnimationResource, AnimationState,
+    FrameMarker, PlaybackMode,
+};
+use crate::game::movement::movement::Direction;
+
+#[derive(Default)]
+pub struct AnimationLibraryLoader;
+
+#[derive(Deserialize, Debug)]
+pub struct AnimationLibraryDescriptor {
+    #[serde(default)]
+    pub mirrored: bool,
+    pub sheets: HashMap<String, SheetDescriptor>,
+    pub clips: Vec<ClipDescriptor>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SheetDescriptor {
+    pub texture: String,
+    pub tile_size: (u32, u32),
+    pub columns: u32,
+    pub rows: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ClipDescriptor {
+    pub state: AnimationState,
+    #[serde(default)]
+    pub direction: Direction,
+    pub sheet: String,
+    pub first: usize,
+    pub last: usize,
+    pub frame_millis: u64,
+    #[serde(default)]
+    pub frame_durations: Vec<u64>,
+    #[serde(default)]
+    pub mode: PlaybackMode,
+    #[serde(default)]
+    pub markers: Vec<FrameMarker>,
+}
+
+impl ClipDescriptor {
+    pub fn name(&self) -> String {
+        format!("{:?}/{:?}", self.state, self.direction)
+    }
+
+    pub fn frame_durations(&self) -> Vec<Duration> {
+        self.frame_durations
+            .iter()
+            .map(|millis| Duration::from_millis(*millis))
+            .collect()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnimationLibraryLoaderError {
+    #[error("could not read animation library: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse animation library: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("clip {clip} references unknown sheet '{sheet}'")]
+    UnknownSheet { clip: String, sheet: String },
+    #[error("clip {clip} has first frame {first} after last frame {last}")]
+    InvalidRange { clip: String, first: usize, last: usize },
+    #[error("clip {clip} ends at frame {last} but sheet '{sheet}' has only {frames} frames")]
+    OutOfBounds { clip: String, sheet: String, last: usize, frames: usize },
+    #[error("clip {clip} has zero frame duration")]
+    ZeroDuration { clip: String },
+    #[error("clip {clip} has {durations} frame durations for {frames} frames")]
+    FrameDurationsMismatch { clip: String, durations: usize, frames: usize },
+    #[error("clip {clip} has marker '{marker}' on frame {frame} outside of the clip")]
+    MarkerOutOfRange { clip: String, marker: String, frame: usize },
+    #[error("clip {clip} is declared more than once")]
+    DuplicateClip { clip: String },
+    #[error("library has no Idle clip to spawn characters with")]
+    MissingIdleClip,
+}
+
+impl AssetLoader for AnimationLibraryLoader {
+    type Asset = AnimationLibrary;
+    type Settings = ();
+    type Error = AnimationLibraryLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let descriptor: AnimationLibraryDescriptor = ron::de::from_bytes(&bytes)?;
+
+        validate(&descriptor)?;
+
+        let mut resources = HashMap::new();
+        for (name, sheet) in descriptor.sheets.iter() {
+            let layout = TextureAtlasLayout::from_grid(
+                UVec2::new(sheet.tile_size.0, sheet.tile_size.1),
+                sheet.columns,
+                sheet.rows,
+                None,
+                None,
+            );
+            let resource = AnimationResource::new(
+                load_context.load(sheet.texture.clone()),
+                load_context.add_labeled_asset(format!("layout/{}", name), layout),
+            );
+            resources.insert(name.clone(), resource);
+        }
+
+        let clips = descriptor
+            .clips
+            .iter()
+            .map(|clip| {
+                let resource = &resources[&clip.sheet];
+                (
+                    (clip.state, clip.direction),
+                    (
+                        AnimationClipResource::new(
+                            AnimationIndices::new(clip.first, clip.last),
+                            clip.frame_millis,
+                            clip.mode,
+                        )
+                        .with_frame_durations(clip.frame_durations())
+                        .with_markers(clip.markers.clone()),
+                        AnimationResource::new(resource.texture.clone(), resource.atlas_layout.clone()),
+                    ),
+                )
+            })
+            .collect();
+
+        Ok(AnimationLibrary {
+            clips,
+            mirrored: descriptor.mirrored,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+fn validate(descriptor: &AnimationLibraryDescriptor) -> Result<(), AnimationLibraryLoaderError> {
+    if !descriptor.clips.iter().any(|clip| clip.state == AnimationState::Idle) {
+        return Err(AnimationLibraryLoaderError::MissingIdleClip);
+    }
+
+    let mut declared = Vec::new();
+
+    for clip in descriptor.clips.iter() {
+        let Some(sheet) = descriptor.sheets.get(&clip.sheet) else {
+            return Err(AnimationLibraryLoaderError::UnknownSheet {
+                clip: clip.name(),
+                sheet: clip.sheet.clone(),
+            });
+        };
+
+        if clip.first > clip.last {
+            return Err(AnimationLibraryLoaderError::InvalidRange {
+                clip: clip.name(),
+                first: clip.first,
+                last: clip.last,
+            });
+        }
+
+        let frames = (sheet.columns * sheet.rows) as usize;
+        if clip.last >= frames {
+            return Err(AnimationLibraryLoaderError::OutOfBounds {
+                clip: clip.name(),
+                sheet: clip.sheet.clone(),
+                last: clip.last,
+                frames,
+            });
+        }
+
+        if clip.frame_millis == 0 || clip.frame_durations.contains(&0) {
+            return Err(AnimationLibraryLoaderError::ZeroDuration { clip: clip.name() });
+        }
+
+        let clip_frames = clip.last - clip.first + 1;
+        if !clip.frame_durations.is_empty() && clip.frame_durations.len() != clip_frames {
+            return Err(AnimationLibraryLoaderError::FrameDurationsMismatch {
+                clip: clip.name(),
+                durations: clip.frame_durations.len(),
+                frames: clip_frames,
+            });
+        }
+
+        if let Some(marker) = clip.markers.iter().find(|marker| clip.first + marker.frame > clip.last) {
+            return Err(AnimationLibraryLoaderError::MarkerOutOfRange {
+                clip: clip.name(),
+                marker: marker.name.clone(),
+                frame: marker.frame,
+            });
+        }
+
+        if declared.contains(&(clip.state, clip.direction)) {
+            return Err(AnimationLibraryLoaderError::DuplicateClip { clip: clip.name() });
+        }
+        declared.push((clip.state, clip.direction));
+    }
+
+    Ok(())
+}
@@ -1 +1,4 @@
 pub mod animation;
+pub mod aseprite;
+pub mod loader;
+pub mod state_machine;
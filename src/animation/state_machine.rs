@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{Component, Entity, Event};
+
+use crate::animation::animation::AnimationState;
+use crate::game::movement::movement::Direction;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum TransitionRule {
+    Immediate,
+    AfterClip,
+    Blocked,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct AnimationStateMachine {
+    pub state: AnimationState,
+    pub direction: Direction,
+    pub fallback: AnimationState,
+    pub queued: Option<(AnimationState, Direction)>,
+    pub transitions: HashMap<(AnimationState, AnimationState), TransitionRule>,
+    held: bool,
+}
+
+impl Default for AnimationStateMachine {
+    fn default() -> Self {
+        Self {
+            state: AnimationState::Idle,
+            direction: Direction::Zero,
+            fallback: AnimationState::Idle,
+            queued: None,
+            transitions: HashMap::new(),
+            held: false,
+        }
+    }
+}
+
+impl AnimationStateMachine {
+    // Attacks requested while flinching play once the Hurt clip ends, nothing leaves Dead.
+    pub fn combatant() -> Self {
+        let mut state_machine = Self::default();
+        for state in AnimationState::STATES {
+            if state.is_attack() {
+                state_machine = state_machine.with_transition(AnimationState::Hurt, state, TransitionRule::AfterClip);
+            }
+            state_machine = state_machine.with_transition(AnimationState::Dead, state, TransitionRule::Blocked);
+        }
+        state_machine
+    }
+
+    pub fn with_transition(mut self, from: AnimationState, to: AnimationState, rule: TransitionRule) -> Self {
+        self.transitions.insert((from, to), rule);
+        self
+    }
+
+    pub fn with_fallback(mut self, fallback: AnimationState) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held
+    }
+
+    pub fn rule(&self, to: AnimationState) -> TransitionRule {
+        if let Some(rule) = self.transitions.get(&(self.state, to)) {
+            return *rule;
+        }
+
+        if self.state.is_terminal() {
+            return TransitionRule::Blocked;
+        }
+
+        if self.state.is_one_shot() && to.priority() <= self.state.priority() {
+            return TransitionRule::Blocked;
+        }
+
+        TransitionRule::Immediate
+    }
+
+    pub fn request(&mut self, state: AnimationState, direction: Direction) -> bool {
+        if !state.is_one_shot() && self.state == state && self.direction == direction {
+            return false;
+        }
+
+        match self.rule(state) {
+            TransitionRule::Immediate => {
+                self.enter(state, direction);
+                true
+            }
+            TransitionRule::AfterClip => {
+                self.queued = Some((state, direction));
+                false
+            }
+            TransitionRule::Blocked => false,
+        }
+    }
+
+    pub fn finish(&mut self, can_enter: impl Fn(AnimationState, Direction) -> bool) -> Option<(AnimationState, Direction)> {
+        if self.held {
+            return None;
+        }
+
+        let finished = (self.state, self.direction);
+        if self.state.is_terminal() {
+            self.held = true;
+            return Some(finished);
+        }
+
+        let (state, direction) = self
+            .queued
+            .take()
+            .filter(|(state, direction)| can_enter(*state, *direction))
+            .unwrap_or((self.fallback, self.direction));
+        self.enter(state, direction);
+
+        Some(finished)
+    }
+
+    fn enter(&mut self, state: AnimationState, direction: Direction) {
+        self.state = state;
+        self.direction = direction;
+        self.queued = None;
+        self.held = false;
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct AnimationFinishedEvent {
+    pub entity: Entity,
+    pub state: AnimationState,
+    pub direction: Direction,
+}
+
+impl AnimationFinishedEvent {
+    pub fn new(entity: &Entity, state: AnimationState, direction: Direction) -> Self {
+        Self {
+            entity: *entity,
+            state,
+            direction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combatant_plays_attack_after_hurt() {
+        let mut state_machine = AnimationStateMachine::combatant();
+        assert!(state_machine.request(AnimationState::Hurt, Direction::Right));
+
+        assert!(!state_machine.request(AnimationState::Attack, Direction::Left));
+        assert_eq!(state_machine.state, AnimationState::Hurt);
+        assert_eq!(state_machine.queued, Some((AnimationState::Attack, Direction::Left)));
+
+        assert_eq!(state_machine.finish(|_, _| true), Some((AnimationState::Hurt, Direction::Right)));
+        assert_eq!((state_machine.state, state_machine.direction), (AnimationState::Attack, Direction::Left));
+    }
+
+    #[test]
+    fn combatant_stays_dead() {
+        let mut state_machine = AnimationStateMachine::combatant();
+        assert!(state_machine.request(AnimationState::Dead, Direction::Zero));
+
+        for state in AnimationState::STATES {
+            assert_eq!(state_machine.rule(state), TransitionRule::Blocked);
+            assert!(!state_machine.request(state, Direction::Left));
+        }
+
+        assert_eq!(state_machine.finish(|_, _| true), Some((AnimationState::Dead, Direction::Zero)));
+        assert!(state_machine.is_held());
+        assert_eq!(state_machine.state, AnimationState::Dead);
+    }
+}
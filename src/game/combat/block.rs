@@ -0,0 +1,273 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{
+    in_state, info, App, Commands, Component, Entity, Event, EventReader, EventWriter,
+    GlobalTransform, IntoSystemConfigs, Plugin, Query, Res, Time, Timer, TimerMode, Update, Vec2,
+    With, Without,
+};
+
+use crate::animation::animation::{AnimationState, ClipChangeEvent};
+use crate::animation::state_machine::{AnimationFinishedEvent, AnimationStateMachine, TransitionRule};
+use crate::game::combat::combat::{apply_damage, DamageEvent, Dead};
+use crate::game::combat::combo::Combo;
+use crate::game::game::GameState;
+use crate::game::movement::movement::{Direction, Facing, KnockbackEvent, MovementProfile};
+use crate::AppState;
+
+const PARRY_KNOCKBACK_STRENGTH: f32 = 300.0;
+
+type BlockerQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static BlockProfile,
+        Option<&'static Facing>,
+        Option<&'static mut MovementProfile>,
+        Option<&'static Blocking>,
+        Option<&'static AnimationStateMachine>,
+    ),
+    Without<Dead>,
+>;
+
+pub struct BlockPlugin;
+
+impl Plugin for BlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BlockEvent>()
+            .add_event::<ParryEvent>()
+            .add_systems(
+                Update,
+                (
+                    (handle_block_events, tick_parry_windows).chain().before(apply_damage),
+                    (stagger_parried_attackers, recover_from_stagger, restore_shield_pose).chain().after(apply_damage),
+                )
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct BlockProfile {
+    pub reduction: f32,
+    pub arc: f32,
+    pub speed_multiplier: f32,
+    pub parry_window: f32,
+    pub stagger: f32,
+}
+
+impl Default for BlockProfile {
+    fn default() -> Self {
+        Self {
+            reduction: 1.0,
+            arc: 0.5,
+            speed_multiplier: 0.4,
+            parry_window: 0.15,
+            stagger: 0.6,
+        }
+    }
+}
+
+impl BlockProfile {
+    pub fn covers(&self, facing: Vec2, towards_attacker: Vec2) -> bool {
+        facing.dot(towards_attacker.normalize_or_zero()) >= self.arc
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Blocking {
+    pub parry: Timer,
+    pub max_speed: Option<f32>,
+}
+
+impl Blocking {
+    pub fn can_parry(&self) -> bool {
+        !self.parry.finished()
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Staggered(pub Timer);
+
+#[derive(Event, Debug)]
+pub struct BlockEvent {
+    pub entity: Entity,
+    pub raised: bool,
+}
+
+impl BlockEvent {
+    pub fn raise(entity: &Entity) -> Self {
+        Self {
+            entity: *entity,
+            raised: true,
+        }
+    }
+
+    pub fn lower(entity: &Entity) -> Self {
+        Self {
+            entity: *entity,
+            raised: false,
+        }
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct ParryEvent {
+    pub defender: Entity,
+    pub attacker: Entity,
+}
+
+#[derive(SystemParam)]
+pub struct Guards<'w, 's> {
+    blocking_query: Query<'w, 's, (&'static Blocking, &'static BlockProfile, &'static Facing, &'static GlobalTransform)>,
+    transform_query: Query<'w, 's, &'static GlobalTransform>,
+    parry_writer: EventWriter<'w, ParryEvent>,
+}
+
+impl Guards<'_, '_> {
+    pub fn mitigate(&mut self, damage_event: &DamageEvent) -> f32 {
+        let Some(source) = damage_event.source else {
+            return damage_event.amount;
+        };
+
+        let (Ok((blocking, profile, facing, transform)), Ok(source_transform)) = (
+            self.blocking_query.get(damage_event.target),
+            self.transform_query.get(source),
+        ) else {
+            return damage_event.amount;
+        };
+
+        let towards_attacker = (source_transform.translation() - transform.translation()).truncate();
+        if !profile.covers(facing.0.to_vec2(), towards_attacker) {
+            return damage_event.amount;
+        }
+
+        if blocking.can_parry() {
+            info!("Entity: {:?} parried: {:?}", damage_event.target, source);
+            self.parry_writer.send(ParryEvent {
+                defender: damage_event.target,
+                attacker: source,
+            });
+            return 0.0;
+        }
+
+        info!("Entity: {:?} blocked: {:?}", damage_event.target, source);
+        damage_event.amount * (1.0 - profile.reduction)
+    }
+}
+
+pub fn handle_block_events(
+    mut commands: Commands,
+    mut block_reader: EventReader<BlockEvent>,
+    mut query: BlockerQuery,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
+) {
+    for block_event in block_reader.read() {
+        let Ok((profile, facing, movement_profile, blocking, state_machine)) = query.get_mut(block_event.entity) else {
+            continue;
+        };
+        let direction = facing.map(|facing| facing.0).unwrap_or(Direction::Zero);
+
+        let can_raise = !state_machine
+            .is_some_and(|state_machine| state_machine.rule(AnimationState::Shield) != TransitionRule::Immediate);
+
+        match (block_event.raised, blocking) {
+            (true, None) if !can_raise => {
+                info!("Entity: {:?} can't raise block during: {:?}", block_event.entity, state_machine.map(|it| it.state));
+            }
+            (true, None) => {
+                info!("Entity: {:?} raised block towards: {:?}", block_event.entity, direction);
+                let max_speed = movement_profile.map(|mut movement_profile| {
+                    let max_speed = movement_profile.max_speed;
+                    movement_profile.max_speed *= profile.speed_multiplier;
+                    max_speed
+                });
+
+                commands.entity(block_event.entity).insert(Blocking {
+                    parry: Timer::from_seconds(profile.parry_window, TimerMode::Once),
+                    max_speed,
+                });
+                clip_event_writer.send(ClipChangeEvent::new(&block_event.entity, AnimationState::Shield, direction));
+            }
+            (false, Some(blocking)) => {
+                info!("Entity: {:?} lowered block", block_event.entity);
+                if let (Some(mut movement_profile), Some(max_speed)) = (movement_profile, blocking.max_speed) {
+                    movement_profile.max_speed = max_speed;
+                }
+
+                commands.entity(block_event.entity).remove::<Blocking>();
+                clip_event_writer.send(ClipChangeEvent::new(&block_event.entity, AnimationState::Idle, direction));
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn tick_parry_windows(mut query: Query<&mut Blocking>, time: Res<Time>) {
+    for mut blocking in query.iter_mut() {
+        blocking.parry.tick(time.delta());
+    }
+}
+
+pub fn stagger_parried_attackers(
+    mut commands: Commands,
+    mut parry_reader: EventReader<ParryEvent>,
+    defender_query: Query<(&BlockProfile, &GlobalTransform)>,
+    mut attacker_query: Query<(&GlobalTransform, Option<&Facing>, Option<&mut Combo>)>,
+    mut knockback_event_writer: EventWriter<KnockbackEvent>,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
+) {
+    for parry_event in parry_reader.read() {
+        let (Ok((profile, defender_transform)), Ok((attacker_transform, facing, combo))) = (
+            defender_query.get(parry_event.defender),
+            attacker_query.get_mut(parry_event.attacker),
+        ) else {
+            continue;
+        };
+
+        info!("Entity: {:?} staggered for {}s", parry_event.attacker, profile.stagger);
+        if let Some(mut combo) = combo {
+            combo.reset();
+        }
+
+        commands
+            .entity(parry_event.attacker)
+            .insert(Staggered(Timer::from_seconds(profile.stagger, TimerMode::Once)));
+
+        let direction = (attacker_transform.translation() - defender_transform.translation()).truncate();
+        knockback_event_writer.send(KnockbackEvent::new(
+            &parry_event.attacker,
+            direction,
+            PARRY_KNOCKBACK_STRENGTH,
+            profile.stagger,
+        ));
+
+        let facing = facing.map(|facing| facing.0).unwrap_or(Direction::Zero);
+        clip_event_writer.send(ClipChangeEvent::new(&parry_event.attacker, AnimationState::Hurt, facing));
+    }
+}
+
+pub fn restore_shield_pose(
+    query: Query<Option<&Facing>, With<Blocking>>,
+    mut finished_reader: EventReader<AnimationFinishedEvent>,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
+) {
+    for finished_event in finished_reader.read() {
+        if finished_event.state == AnimationState::Dead {
+            continue;
+        }
+
+        if let Ok(facing) = query.get(finished_event.entity) {
+            let direction = facing.map(|facing| facing.0).unwrap_or(finished_event.direction);
+            clip_event_writer.send(ClipChangeEvent::new(&finished_event.entity, AnimationState::Shield, direction));
+        }
+    }
+}
+
+pub fn recover_from_stagger(mut commands: Commands, mut query: Query<(Entity, &mut Staggered)>, time: Res<Time>) {
+    for (entity, mut staggered) in query.iter_mut() {
+        if staggered.0.tick(time.delta()).finished() {
+            info!("Entity: {:?} recovered from stagger", entity);
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{
+    in_state, info, App, Commands, Component, DespawnRecursiveExt, Entity, Event, EventReader,
+    EventWriter, IntoSystemConfigs, Or, Plugin, Query, Update, With,
+};
+
+use crate::animation::animation::{AnimationState, ClipChangeEvent};
+use crate::animation::state_machine::AnimationFinishedEvent;
+use crate::game::combat::block::{BlockPlugin, Blocking, Guards, Staggered};
+use crate::game::combat::combo::ComboPlugin;
+use crate::game::combat::hitbox::HitboxPlugin;
+use crate::game::combat::reaction::ReactionPlugin;
+use crate::game::game::GameState;
+use crate::game::movement::movement::{CharacterVelocity, DashProfile, Dashing, Direction, Facing, Invulnerable, Knockback, MovementProfile};
+use crate::AppState;
+
+type Immune = Or<(With<Invulnerable>, With<Dead>)>;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<HurtEvent>()
+            .add_event::<DeathEvent>()
+            .add_plugins((HitboxPlugin, ComboPlugin, BlockPlugin, ReactionPlugin))
+            .add_systems(
+                Update,
+                (apply_damage, despawn_dead)
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DamageKind {
+    #[default]
+    Melee,
+    Contact,
+    Explosion,
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct Damage {
+    pub amount: f32,
+    pub kind: DamageKind,
+}
+
+impl Damage {
+    pub fn new(amount: f32, kind: DamageKind) -> Self {
+        Self { amount, kind }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Dead;
+
+#[derive(Component, Debug)]
+pub struct DespawnOnDeath;
+
+#[derive(Event, Debug)]
+pub struct DamageEvent {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+}
+
+impl DamageEvent {
+    pub fn new(source: Option<Entity>, target: &Entity, damage: &Damage) -> Self {
+        Self {
+            source,
+            target: *target,
+            amount: damage.amount,
+            kind: damage.kind,
+        }
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct HurtEvent {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+    pub amount: f32,
+}
+
+#[derive(Event, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+}
+
+#[derive(SystemParam)]
+pub struct DamageWriters<'w> {
+    hurt: EventWriter<'w, HurtEvent>,
+    death: EventWriter<'w, DeathEvent>,
+    clip: EventWriter<'w, ClipChangeEvent>,
+}
+
+pub fn apply_damage(
+    mut commands: Commands,
+    mut damage_reader: EventReader<DamageEvent>,
+    mut query: Query<(&mut Health, Option<&Facing>, Option<&mut CharacterVelocity>)>,
+    immune_query: Query<(), Immune>,
+    mut guards: Guards,
+    mut writers: DamageWriters,
+) {
+    for damage_event in damage_reader.read() {
+        if immune_query.contains(damage_event.target) {
+            continue;
+        }
+
+        let Ok((mut health, facing, velocity)) = query.get_mut(damage_event.target) else {
+            continue;
+        };
+
+        let amount = guards.mitigate(damage_event);
+        if amount <= 0.0 {
+            continue;
+        }
+
+        health.current = (health.current - amount).max(0.0);
+        info!(
+            "Entity: {:?} took {} {:?} damage from: {:?}, health: {}/{}",
+            damage_event.target, amount, damage_event.kind, damage_event.source, health.current, health.max
+        );
+
+        if !health.is_dead() {
+            writers.hurt.send(HurtEvent {
+                entity: damage_event.target,
+                source: damage_event.source,
+                amount,
+            });
+            continue;
+        }
+
+        info!("Entity: {:?} died", damage_event.target);
+        if let Some(mut velocity) = velocity {
+            velocity.0 = Default::default();
+        }
+
+        commands
+            .entity(damage_event.target)
+            .remove::<(MovementProfile, DashProfile, Dashing, Knockback, Blocking, Staggered)>()
+            .insert(Dead);
+
+        let direction = facing.map(|facing| facing.0).unwrap_or(Direction::Zero);
+        writers.clip.send(ClipChangeEvent::new(&damage_event.target, AnimationState::Dead, direction));
+        writers.death.send(DeathEvent {
+            entity: damage_event.target,
+            killer: damage_event.source,
+        });
+    }
+}
+
+pub fn despawn_dead(
+    mut commands: Commands,
+    mut finished_reader: EventReader<AnimationFinishedEvent>,
+    query: Query<(), (With<Dead>, With<DespawnOnDeath>)>,
+) {
+    for finished_event in finished_reader.read() {
+        if finished_event.state == AnimationState::Dead && query.contains(finished_event.entity) {
+            info!("Despawning dead entity: {:?}", finished_event.entity);
+            commands.entity(finished_event.entity).despawn_recursive();
+        }
+    }
+}
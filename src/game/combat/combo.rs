@@ -0,0 +1,201 @@
+use bevy::prelude::{
+    in_state, info, App, Component, Entity, Event, EventReader, EventWriter, IntoSystemConfigs,
+    Plugin, Query, Res, Time, Timer, TimerMode, Update, Vec2, Without,
+};
+
+use crate::animation::animation::{AnimationState, ClipChangeEvent};
+use crate::animation::state_machine::{AnimationFinishedEvent, AnimationStateMachine};
+use crate::game::combat::block::Staggered;
+use crate::game::combat::combat::{apply_damage, Dead, HurtEvent};
+use crate::game::combat::hitbox::{spawn_attack_hitboxes, AttackProfile};
+use crate::game::game::GameState;
+use crate::AppState;
+
+type ComboQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static ComboChain, &'static mut Combo, &'static mut AttackProfile, Option<&'static AnimationStateMachine>),
+    (Without<Dead>, Without<Staggered>),
+>;
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AttackEvent>().add_systems(
+            Update,
+            advance_combos
+                .before(spawn_attack_hitboxes)
+                .before(apply_damage)
+                .run_if(in_state(AppState::Game))
+                .run_if(in_state(GameState::Running)),
+        );
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct AttackEvent {
+    pub entity: Entity,
+    pub direction: Vec2,
+}
+
+impl AttackEvent {
+    pub fn new(entity: &Entity, direction: Vec2) -> Self {
+        Self {
+            entity: *entity,
+            direction,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ComboAttack {
+    pub state: AnimationState,
+    pub profile: AttackProfile,
+    pub recovery: f32,
+}
+
+impl ComboAttack {
+    pub fn new(state: AnimationState, profile: AttackProfile, recovery: f32) -> Self {
+        Self {
+            state,
+            profile,
+            recovery,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct ComboChain {
+    pub attacks: Vec<ComboAttack>,
+    pub window: f32,
+}
+
+impl ComboChain {
+    pub fn new(attacks: Vec<ComboAttack>, window: f32) -> Self {
+        Self { attacks, window }
+    }
+
+    pub fn next_step(&self, step: Option<usize>) -> usize {
+        match step {
+            Some(step) if step + 1 < self.attacks.len() => step + 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Combo {
+    pub step: Option<usize>,
+    pub attacking: bool,
+    pub started: bool,
+    pub recovery: Timer,
+    pub window: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        let mut recovery = Timer::from_seconds(0.0, TimerMode::Once);
+        recovery.tick(Default::default());
+        Self {
+            step: None,
+            attacking: false,
+            started: false,
+            recovery,
+            window: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+}
+
+impl Combo {
+    pub fn is_busy(&self) -> bool {
+        self.attacking || !self.recovery.finished()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+pub fn advance_combos(
+    mut query: ComboQuery,
+    mut attack_reader: EventReader<AttackEvent>,
+    mut finished_reader: EventReader<AnimationFinishedEvent>,
+    mut hurt_reader: EventReader<HurtEvent>,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
+    time: Res<Time>,
+) {
+    for hurt_event in hurt_reader.read() {
+        if let Ok((entity, _, mut combo, _, _)) = query.get_mut(hurt_event.entity) {
+            info!("Combo of: {:?} interrupted by a hit", entity);
+            combo.reset();
+        }
+    }
+
+    for finished_event in finished_reader.read() {
+        if !finished_event.state.is_attack() {
+            continue;
+        }
+
+        let Ok((_, chain, mut combo, _, _)) = query.get_mut(finished_event.entity) else {
+            continue;
+        };
+
+        let Some(attack) = combo.step.and_then(|step| chain.attacks.get(step)) else {
+            continue;
+        };
+
+        combo.recovery = Timer::from_seconds(attack.recovery, TimerMode::Once);
+        combo.window = Timer::from_seconds(chain.window, TimerMode::Once);
+        combo.attacking = false;
+    }
+
+    for (entity, _, mut combo, _, state_machine) in query.iter_mut() {
+        if combo.attacking {
+            if !combo.started && state_machine.is_some_and(|state_machine| !is_attacking(state_machine)) {
+                info!("Combo of: {:?} interrupted", entity);
+                combo.reset();
+            }
+            combo.started = false;
+            continue;
+        }
+
+        if !combo.recovery.tick(time.delta()).finished() {
+            continue;
+        }
+
+        if combo.step.is_some() && combo.window.tick(time.delta()).finished() {
+            info!("Combo of: {:?} timed out", entity);
+            combo.reset();
+        }
+    }
+
+    for attack_event in attack_reader.read() {
+        let Ok((entity, chain, mut combo, mut profile, _)) = query.get_mut(attack_event.entity) else {
+            continue;
+        };
+
+        if chain.attacks.is_empty() {
+            continue;
+        }
+
+        if combo.is_busy() {
+            info!("Combo of: {:?} is busy, ignoring attack", entity);
+            continue;
+        }
+
+        let step = chain.next_step(combo.step);
+        let attack = chain.attacks[step];
+        *profile = attack.profile;
+        combo.step = Some(step);
+        combo.attacking = true;
+        combo.started = true;
+
+        info!("Combo of: {:?} attack {}: {:?}", entity, step + 1, attack.state);
+        clip_event_writer.send(ClipChangeEvent::towards(&entity, attack.state, attack_event.direction));
+    }
+}
+
+fn is_attacking(state_machine: &AnimationStateMachine) -> bool {
+    state_machine.state.is_attack() || state_machine.queued.is_some_and(|(state, _)| state.is_attack())
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use bevy::prelude::{
+    in_state, info, App, Commands, Component, DespawnRecursiveExt, Entity, EventReader, EventWriter,
+    GlobalTransform, IntoSystemConfigs, OnExit, Plugin, Quat, Query, Res, Time, Timer, TimerMode,
+    Transform, TransformBundle, Update, Vec2, With, Without,
+};
+use bevy_rapier2d::prelude::{ActiveCollisionTypes, ActiveEvents, Collider, CollisionEvent, Sensor};
+
+use crate::animation::animation::{AnimationMarkerEvent, HIT_MARKER};
+use crate::animation::state_machine::AnimationFinishedEvent;
+use crate::game::combat::combat::{apply_damage, Damage, DamageEvent, Dead, Health};
+use crate::game::game::GameState;
+use crate::game::movement::movement::{Direction, Facing};
+use crate::AppState;
+
+pub struct HitboxPlugin;
+
+impl Plugin for HitboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_attack_hitboxes, follow_attacker, detect_hitbox_hits, despawn_expired_hitboxes)
+                .chain()
+                .before(apply_damage)
+                .run_if(in_state(AppState::Game))
+                .run_if(in_state(GameState::Running)),
+        )
+        .add_systems(OnExit(AppState::Game), despawn_hitboxes);
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct AttackProfile {
+    pub damage: Damage,
+    pub reach: f32,
+    pub size: Vec2,
+    pub active: f32,
+}
+
+impl AttackProfile {
+    pub fn new(damage: Damage, reach: f32, size: Vec2, active: f32) -> Self {
+        Self {
+            damage,
+            reach,
+            size,
+            active,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Hitbox {
+    pub owner: Entity,
+    pub damage: Damage,
+    pub offset: Vec2,
+    pub timer: Timer,
+    pub hits: HashSet<Entity>,
+}
+
+impl Hitbox {
+    pub fn new(owner: &Entity, profile: &AttackProfile, direction: Vec2) -> Self {
+        Self {
+            owner: *owner,
+            damage: profile.damage,
+            offset: direction * profile.reach,
+            timer: Timer::from_seconds(profile.active, TimerMode::Once),
+            hits: HashSet::new(),
+        }
+    }
+}
+
+pub fn spawn_attack_hitboxes(
+    mut commands: Commands,
+    mut marker_reader: EventReader<AnimationMarkerEvent>,
+    attacker_query: Query<(&GlobalTransform, &AttackProfile, Option<&Facing>), Without<Dead>>,
+) {
+    for marker_event in marker_reader.read() {
+        if marker_event.marker != HIT_MARKER || !marker_event.state.is_attack() {
+            continue;
+        }
+
+        let Ok((attacker_transform, profile, facing)) = attacker_query.get(marker_event.entity) else {
+            continue;
+        };
+
+        let direction = match marker_event.direction {
+            Direction::Zero => facing.map(|facing| facing.0).unwrap_or(Direction::Right),
+            direction => direction,
+        }
+        .to_vec2();
+
+        let hitbox = Hitbox::new(&marker_event.entity, profile, direction);
+        let translation = attacker_transform.translation() + hitbox.offset.extend(0.0);
+        info!("Spawning hitbox for: {:?} towards: {:?}", marker_event.entity, direction);
+
+        commands.spawn((
+            TransformBundle::from_transform(
+                Transform::from_translation(translation)
+                    .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x))),
+            ),
+            Collider::cuboid(profile.size.x / 2.0, profile.size.y / 2.0),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+            hitbox,
+        ));
+    }
+}
+
+pub fn follow_attacker(
+    mut hitbox_query: Query<(&Hitbox, &mut Transform)>,
+    attacker_query: Query<&GlobalTransform, Without<Hitbox>>,
+) {
+    for (hitbox, mut transform) in hitbox_query.iter_mut() {
+        if let Ok(attacker_transform) = attacker_query.get(hitbox.owner) {
+            transform.translation = attacker_transform.translation() + hitbox.offset.extend(0.0);
+        }
+    }
+}
+
+pub fn detect_hitbox_hits(
+    mut collision_reader: EventReader<CollisionEvent>,
+    mut hitbox_query: Query<&mut Hitbox>,
+    target_query: Query<(), (With<Health>, Without<Dead>)>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for collision_event in collision_reader.read() {
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            continue;
+        };
+
+        let (hitbox_entity, target) = if hitbox_query.contains(*first) {
+            (*first, *second)
+        } else {
+            (*second, *first)
+        };
+
+        let Ok(mut hitbox) = hitbox_query.get_mut(hitbox_entity) else {
+            continue;
+        };
+
+        if target == hitbox.owner || !target_query.contains(target) || !hitbox.hits.insert(target) {
+            continue;
+        }
+
+        info!("Hitbox of: {:?} hit: {:?}", hitbox.owner, target);
+        damage_writer.send(DamageEvent::new(Some(hitbox.owner), &target, &hitbox.damage));
+    }
+}
+
+pub fn despawn_expired_hitboxes(
+    mut commands: Commands,
+    mut hitbox_query: Query<(Entity, &mut Hitbox)>,
+    mut finished_reader: EventReader<AnimationFinishedEvent>,
+    owner_query: Query<(), Without<Dead>>,
+    time: Res<Time>,
+) {
+    let finished: Vec<Entity> = finished_reader
+        .read()
+        .filter(|finished_event| finished_event.state.is_attack())
+        .map(|finished_event| finished_event.entity)
+        .collect();
+
+    for (entity, mut hitbox) in hitbox_query.iter_mut() {
+        hitbox.timer.tick(time.delta());
+
+        if hitbox.timer.finished() || finished.contains(&hitbox.owner) || !owner_query.contains(hitbox.owner) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn despawn_hitboxes(mut commands: Commands, query: Query<Entity, With<Hitbox>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
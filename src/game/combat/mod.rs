@@ -0,0 +1,5 @@
+pub mod block;
+pub mod combat;
+pub mod combo;
+pub mod hitbox;
+pub mod reaction;
@@ -0,0 +1,140 @@
+use bevy::color::Color;
+use bevy::prelude::{
+    in_state, info, App, Commands, Component, Entity, EventReader, EventWriter, IntoSystemConfigs,
+    OnExit, Plugin, Query, Real, Res, ResMut, Resource, Sprite, Time, Timer, TimerMode, Update,
+    Virtual, Without,
+};
+
+use crate::animation::animation::{AnimationState, ClipChangeEvent};
+use crate::game::combat::combat::{apply_damage, Dead, HurtEvent};
+use crate::game::movement::movement::{Direction, Facing, Invulnerable};
+use crate::AppState;
+
+type ReactionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static HitReaction,
+        Option<&'static Facing>,
+        Option<&'static mut Sprite>,
+        Option<&'static HitFlash>,
+        Option<&'static Invulnerable>,
+    ),
+    Without<Dead>,
+>;
+
+pub struct ReactionPlugin;
+
+impl Plugin for ReactionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitStop>()
+            .add_systems(
+                Update,
+                ((react_to_hits, update_hit_flashes).chain().after(apply_damage), update_hit_stop)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(OnExit(AppState::Game), end_hit_stop);
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct HitReaction {
+    pub invulnerability: f32,
+    pub flash: f32,
+    pub flash_color: Color,
+    pub heavy_hit: f32,
+    pub hit_stop: f32,
+}
+
+impl Default for HitReaction {
+    fn default() -> Self {
+        Self {
+            invulnerability: 0.5,
+            flash: 0.15,
+            flash_color: Color::srgb(1.0, 0.3, 0.3),
+            heavy_hit: 30.0,
+            hit_stop: 0.08,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct HitFlash {
+    pub timer: Timer,
+    pub color: Color,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct HitStop(pub Option<Timer>);
+
+pub fn react_to_hits(
+    mut commands: Commands,
+    mut hurt_reader: EventReader<HurtEvent>,
+    mut query: ReactionQuery,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
+) {
+    for hurt_event in hurt_reader.read() {
+        let Ok((reaction, facing, sprite, flash, invulnerable)) = query.get_mut(hurt_event.entity) else {
+            continue;
+        };
+
+        let direction = facing.map(|facing| facing.0).unwrap_or(Direction::Zero);
+        clip_event_writer.send(ClipChangeEvent::new(&hurt_event.entity, AnimationState::Hurt, direction));
+        if !invulnerable.is_some_and(|invulnerable| invulnerable.0.remaining_secs() >= reaction.invulnerability) {
+            commands
+                .entity(hurt_event.entity)
+                .insert(Invulnerable::from_seconds(reaction.invulnerability));
+        }
+
+        if let Some(mut sprite) = sprite {
+            let color = flash.map(|flash| flash.color).unwrap_or(sprite.color);
+            sprite.color = reaction.flash_color;
+            commands.entity(hurt_event.entity).insert(HitFlash {
+                timer: Timer::from_seconds(reaction.flash, TimerMode::Once),
+                color,
+            });
+        }
+
+        if hurt_event.amount >= reaction.heavy_hit && reaction.hit_stop > 0.0 {
+            info!("Heavy hit on: {:?}, hit-stop for {}s", hurt_event.entity, reaction.hit_stop);
+            hit_stop.0 = Some(Timer::from_seconds(reaction.hit_stop, TimerMode::Once));
+            virtual_time.pause();
+        }
+    }
+}
+
+pub fn update_hit_flashes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash, mut sprite) in query.iter_mut() {
+        if flash.timer.tick(time.delta()).finished() {
+            sprite.color = flash.color;
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
+
+pub fn update_hit_stop(
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    let Some(timer) = hit_stop.0.as_mut() else {
+        return;
+    };
+
+    if timer.tick(real_time.delta()).finished() {
+        hit_stop.0 = None;
+        virtual_time.unpause();
+    }
+}
+
+pub fn end_hit_stop(mut hit_stop: ResMut<HitStop>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if hit_stop.0.take().is_some() {
+        virtual_time.unpause();
+    }
+}
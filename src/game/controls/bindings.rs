@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::{info, warn, KeyCode, Resource};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::game::controls::context::{ContextAction, ContextBindings, InputContext};
+use crate::game::controls::controls::ControlledAction;
+use crate::game::controls::gamepad::GamepadBindings;
+use crate::game::controls::mouse::MouseBindings;
+
+const BINDINGS_DIR: &str = "pipisenok-game";
+const BINDINGS_FILE: &str = "key_bindings.ron";
+
+#[derive(Debug, Error)]
+pub enum KeyBindingsError {
+    #[error("could not find a config directory for key bindings")]
+    NoConfigDir,
+    #[error("could not access key bindings file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse key bindings file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not serialize key bindings: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum KeyBindingConflict {
+    #[error("{key:?} is reserved for {action:?}")]
+    Reserved { key: KeyCode, action: ContextAction },
+    #[error("{key:?} is already bound to {action:?}")]
+    Bound { key: KeyCode, action: ControlledAction },
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    pub bindings: BTreeMap<ControlledAction, Vec<KeyCode>>,
+    #[serde(default)]
+    pub gamepad: GamepadBindings,
+    #[serde(default)]
+    pub gamepad_devices: BTreeMap<String, GamepadBindings>,
+    #[serde(default)]
+    pub mouse: MouseBindings,
+    #[serde(default)]
+    pub contexts: ContextBindings,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::from([
+                (ControlledAction::MoveUp, vec![KeyCode::KeyW, KeyCode::ArrowUp]),
+                (ControlledAction::MoveLeft, vec![KeyCode::KeyA, KeyCode::ArrowLeft]),
+                (ControlledAction::MoveDown, vec![KeyCode::KeyS, KeyCode::ArrowDown]),
+                (ControlledAction::MoveRight, vec![KeyCode::KeyD, KeyCode::ArrowRight]),
+                (ControlledAction::Run, vec![KeyCode::ShiftLeft]),
+                (ControlledAction::Attack, vec![KeyCode::KeyF]),
+                (ControlledAction::Dash, vec![KeyCode::KeyQ]),
+                (ControlledAction::Block, vec![KeyCode::KeyE]),
+            ]),
+            gamepad: GamepadBindings::default(),
+            gamepad_devices: BTreeMap::new(),
+            mouse: MouseBindings::default(),
+            contexts: ContextBindings::default(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn action_for(&self, key: &KeyCode) -> Option<ControlledAction> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn keys_for(&self, action: ControlledAction) -> &[KeyCode] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn primary_key(&self, action: ControlledAction) -> Option<KeyCode> {
+        self.keys_for(action).first().copied()
+    }
+
+    pub fn gamepad_for(&self, device: Option<&str>) -> &GamepadBindings {
+        device
+            .and_then(|name| self.gamepad_devices.get(name))
+            .unwrap_or(&self.gamepad)
+    }
+
+    pub fn conflict(&self, action: ControlledAction, key: KeyCode) -> Option<KeyBindingConflict> {
+        if let Some(reserved) = self.contexts.action_for(InputContext::Gameplay, &key) {
+            return Some(KeyBindingConflict::Reserved { key, action: reserved });
+        }
+
+        self.action_for(&key)
+            .filter(|bound| *bound != action)
+            .map(|bound| KeyBindingConflict::Bound { key, action: bound })
+    }
+
+    pub fn rebind(&mut self, action: ControlledAction, key: KeyCode) -> Result<(), KeyBindingConflict> {
+        if let Some(conflict) = self.conflict(action, key) {
+            return Err(conflict);
+        }
+
+        let keys = self.bindings.entry(action).or_default();
+        keys.retain(|bound| *bound != key);
+        match keys.first_mut() {
+            Some(primary) => *primary = key,
+            None => keys.push(key),
+        }
+
+        Ok(())
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(BINDINGS_DIR).join(BINDINGS_FILE))
+    }
+
+    pub fn load() -> Result<Self, KeyBindingsError> {
+        let path = Self::path().ok_or(KeyBindingsError::NoConfigDir)?;
+        let mut bindings: Self = ron::de::from_str(&fs::read_to_string(path)?)?;
+
+        bindings.add_missing_defaults();
+
+        Ok(bindings)
+    }
+
+    fn add_missing_defaults(&mut self) {
+        let defaults = Self::default();
+        for (action, keys) in defaults.bindings {
+            if self.bindings.contains_key(&action) {
+                continue;
+            }
+
+            match keys.iter().find_map(|key| self.conflict(action, *key)) {
+                Some(conflict) => warn!("Leaving {:?} unbound: {}", action, conflict),
+                None => {
+                    self.bindings.insert(action, keys);
+                }
+            }
+        }
+
+        self.gamepad.add_missing_defaults(&defaults.gamepad);
+        for device in self.gamepad_devices.values_mut() {
+            device.add_missing_defaults(&defaults.gamepad);
+        }
+    }
+
+    pub fn load_or_default() -> Self {
+        match Self::load() {
+            Ok(bindings) => {
+                info!("Loaded key bindings from: {:?}", Self::path());
+                bindings
+            }
+            Err(KeyBindingsError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(error) => {
+                warn!("Using default key bindings: {}", error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), KeyBindingsError> {
+        let path = Self::path().ok_or(KeyBindingsError::NoConfigDir)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&path, ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?)?;
+        info!("Saved key bindings to: {:?}", path);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{info, EventReader, EventWriter, Res, ResMut, Resource, Time};
+
+use crate::game::controls::controls::{
+    ActionDoubleTapEvent, ActionEndEvent, ActionEvent, ActionHoldEvent, ActionStartEvent, Actions,
+    ControlledAction,
+};
+
+const INPUT_BUFFER_WINDOW: Duration = Duration::from_millis(200);
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(250);
+
+#[derive(Resource, Debug, Default)]
+pub struct ActionTimings {
+    pub held: HashMap<ControlledAction, Duration>,
+    pub last_pressed: HashMap<ControlledAction, Duration>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct InputBuffer {
+    pub entries: Vec<(ControlledAction, Duration)>,
+}
+
+impl InputBuffer {
+    pub fn push(&mut self, action: ControlledAction, now: Duration) {
+        self.entries.push((action, now));
+    }
+
+    pub fn expire(&mut self, now: Duration) {
+        self.entries.retain(|(_, pressed_at)| now.saturating_sub(*pressed_at) <= INPUT_BUFFER_WINDOW);
+    }
+
+    pub fn defer(&mut self, action: ControlledAction, now: Duration) {
+        for (buffered, pressed_at) in self.entries.iter_mut() {
+            if *buffered == action {
+                *pressed_at = now;
+            }
+        }
+    }
+
+    pub fn contains(&self, action: ControlledAction) -> bool {
+        self.entries.iter().any(|(buffered, _)| *buffered == action)
+    }
+
+    pub fn consume(&mut self, action: ControlledAction) -> bool {
+        match self.entries.iter().position(|(buffered, _)| *buffered == action) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct ActionPhaseWriters<'w> {
+    start: EventWriter<'w, ActionStartEvent>,
+    hold: EventWriter<'w, ActionHoldEvent>,
+    end: EventWriter<'w, ActionEndEvent>,
+    double_tap: EventWriter<'w, ActionDoubleTapEvent>,
+}
+
+pub fn track_action_phases(
+    mut action_reader: EventReader<ActionEvent>,
+    mut actions: ResMut<Actions>,
+    mut timings: ResMut<ActionTimings>,
+    mut input_buffer: ResMut<InputBuffer>,
+    mut writers: ActionPhaseWriters,
+    time: Res<Time>,
+) {
+    let now = time.elapsed();
+    input_buffer.expire(now);
+
+    let Some(action_event) = action_reader.read().last() else {
+        return;
+    };
+
+    let current: HashSet<ControlledAction> = action_event
+        .actions
+        .iter()
+        .copied()
+        .filter(|action| *action != ControlledAction::None)
+        .collect();
+
+    for action in actions.current_actions.difference(&current) {
+        let held = timings.held.remove(action).unwrap_or_default();
+        info!("Action released: {:?} after {:?}", action, held);
+        writers.end.send(ActionEndEvent::new(*action, held));
+    }
+
+    for action in current.iter() {
+        if actions.current_actions.contains(action) {
+            let held = timings.held.entry(*action).or_default();
+            *held += time.delta();
+            writers.hold.send(ActionHoldEvent {
+                action: *action,
+                held: *held,
+            });
+            continue;
+        }
+
+        info!("Action pressed: {:?}", action);
+        timings.held.insert(*action, Duration::ZERO);
+        writers.start.send(ActionStartEvent { action: *action });
+        input_buffer.push(*action, now);
+
+        let double_tapped = timings
+            .last_pressed
+            .insert(*action, now)
+            .is_some_and(|last_pressed| now.saturating_sub(last_pressed) <= DOUBLE_TAP_WINDOW);
+        if double_tapped {
+            info!("Action double tapped: {:?}", action);
+            writers.double_tap.send(ActionDoubleTapEvent { action: *action });
+            timings.last_pressed.remove(action);
+        }
+    }
+
+    actions.current_actions = current;
+}
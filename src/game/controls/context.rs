@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::{
+    info, ButtonInput, EventReader, EventWriter, Event, KeyCode, Res, ResMut, Resource,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::game::controls::bindings::KeyBindings;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum InputContext {
+    Menu,
+    Gameplay,
+    Paused,
+    Dialogue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ContextAction {
+    Quit,
+    StartGame,
+    OpenMainMenu,
+    TogglePause,
+    CloseDialogue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContextBindings {
+    pub contexts: BTreeMap<InputContext, BTreeMap<ContextAction, Vec<KeyCode>>>,
+}
+
+impl Default for ContextBindings {
+    fn default() -> Self {
+        Self {
+            contexts: BTreeMap::from([
+                (
+                    InputContext::Menu,
+                    BTreeMap::from([
+                        (ContextAction::Quit, vec![KeyCode::Escape]),
+                        (ContextAction::StartGame, vec![KeyCode::KeyG]),
+                    ]),
+                ),
+                (
+                    InputContext::Gameplay,
+                    BTreeMap::from([
+                        (ContextAction::Quit, vec![KeyCode::Escape]),
+                        (ContextAction::TogglePause, vec![KeyCode::Space]),
+                    ]),
+                ),
+                (
+                    InputContext::Paused,
+                    BTreeMap::from([
+                        (ContextAction::Quit, vec![KeyCode::Escape]),
+                        (ContextAction::TogglePause, vec![KeyCode::Space]),
+                        (ContextAction::OpenMainMenu, vec![KeyCode::KeyM]),
+                    ]),
+                ),
+                (
+                    InputContext::Dialogue,
+                    BTreeMap::from([(ContextAction::CloseDialogue, vec![KeyCode::Space, KeyCode::Escape])]),
+                ),
+            ]),
+        }
+    }
+}
+
+impl ContextBindings {
+    pub fn action_for(&self, context: InputContext, key: &KeyCode) -> Option<ContextAction> {
+        self.contexts
+            .get(&context)?
+            .iter()
+            .find(|(_, keys)| keys.contains(key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn keys_for(&self, context: InputContext, action: ContextAction) -> &[KeyCode] {
+        self.contexts
+            .get(&context)
+            .and_then(|actions| actions.get(&action))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct InputContextStack {
+    pub contexts: Vec<InputContext>,
+}
+
+impl InputContextStack {
+    pub fn current(&self) -> Option<InputContext> {
+        self.contexts.last().copied()
+    }
+
+    pub fn push(&mut self, context: InputContext) {
+        info!("Pushing input context: {:?}", context);
+        self.contexts.push(context);
+    }
+
+    pub fn pop(&mut self, context: InputContext) {
+        if let Some(index) = self.contexts.iter().rposition(|it| *it == context) {
+            info!("Popping input context: {:?}", context);
+            self.contexts.remove(index);
+        }
+    }
+}
+
+#[derive(Event, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ContextActionEvent {
+    pub context: InputContext,
+    pub action: ContextAction,
+}
+
+pub fn in_input_context(context: InputContext) -> impl Fn(Res<InputContextStack>) -> bool {
+    move |stack: Res<InputContextStack>| stack.current() == Some(context)
+}
+
+pub fn push_input_context(context: InputContext) -> impl Fn(ResMut<InputContextStack>) {
+    move |mut stack: ResMut<InputContextStack>| stack.push(context)
+}
+
+pub fn pop_input_context(context: InputContext) -> impl Fn(ResMut<InputContextStack>) {
+    move |mut stack: ResMut<InputContextStack>| stack.pop(context)
+}
+
+pub fn emit_context_actions(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    stack: Res<InputContextStack>,
+    bindings: Res<KeyBindings>,
+    mut event_writer: EventWriter<ContextActionEvent>,
+) {
+    let Some(context) = stack.current() else {
+        return;
+    };
+
+    for key in keyboard_input.get_just_pressed() {
+        if let Some(action) = bindings.contexts.action_for(context, key) {
+            info!("Context {:?} action: {:?}", context, action);
+            event_writer.send(ContextActionEvent { context, action });
+        }
+    }
+}
+
+pub fn close_dialogue(mut event_reader: EventReader<ContextActionEvent>, mut stack: ResMut<InputContextStack>) {
+    for event in event_reader.read() {
+        if event.action == ContextAction::CloseDialogue {
+            stack.pop(InputContext::Dialogue);
+        }
+    }
+}
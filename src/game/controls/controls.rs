@@ -1,87 +1,192 @@
 use std::cmp::PartialEq;
-use std::collections::{HashMap, HashSet};
-use std::ops::Add;
+use std::collections::HashSet;
+use std::time::Duration;
 
-use bevy::prelude::{in_state, info, App, ButtonInput, Component, Deref, IntoSystemConfigs, KeyCode, Plugin, Query, Res, ResMut, Resource, Update, Event, EventWriter, Entity};
+use bevy::prelude::{in_state, info, App, ButtonInput, Deref, Vec2, IntoSystemConfigs, DerefMut, KeyCode, Last, OnEnter, OnExit, Plugin, Res, ResMut, Resource, Update, Event, EventWriter};
+use serde::{Deserialize, Serialize};
 
+use crate::game::controls::bindings::KeyBindings;
+use crate::game::controls::buffer::{track_action_phases, ActionTimings, InputBuffer};
+use crate::game::controls::context::{
+    close_dialogue, emit_context_actions, in_input_context, pop_input_context, push_input_context,
+    ContextActionEvent, InputContext, InputContextStack,
+};
+use crate::game::controls::gamepad::{handle_gamepad_connections, ActiveGamepad, GamepadDevices};
+use crate::game::controls::mouse::{
+    clear_blocked_mouse_target, handle_mouse_state, update_cursor_world_position, CursorWorldPosition, MouseState,
+};
+use crate::game::controls::recording::{
+    advance_replay, is_live, is_replaying, record_frame, replay_actions, replay_context_actions,
+    save_recording_on_exit, InputSession,
+};
 use crate::game::game::GameState;
-use crate::game::movement::movement::{Direction, MoveEndEvent};
+use crate::game::random::random::GameRng;
+use crate::game::movement::movement::Direction;
 use crate::AppState;
 
 pub struct ControlsPlugin;
 
 impl Plugin for ControlsPlugin {
     fn build(&self, app: &mut App) {
+        let session = app
+            .world_mut()
+            .remove_resource::<InputSession>()
+            .unwrap_or_else(InputSession::from_args);
+
         app
+            .insert_resource(GameRng::seeded(session.seed()))
+            .insert_resource(session.first_frame_strategy())
+            .insert_resource(session)
             .init_resource::<Actions>()
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<InputContextStack>()
+            .init_resource::<CursorWorldPosition>()
+            .init_resource::<MouseState>()
+            .add_event::<ContextActionEvent>()
+            .insert_resource(KeyBindings::load_or_default())
             .add_event::<ActionEvent>()
+            .init_resource::<ActionTimings>()
+            .init_resource::<InputBuffer>()
+            .add_event::<ActionStartEvent>()
+            .add_event::<ActionHoldEvent>()
             .add_event::<ActionEndEvent>()
+            .add_event::<ActionDoubleTapEvent>()
+            .add_systems(OnEnter(AppState::MainMenu), push_input_context(InputContext::Menu))
+            .add_systems(OnExit(AppState::MainMenu), pop_input_context(InputContext::Menu))
+            .add_systems(OnEnter(AppState::GameOver), push_input_context(InputContext::Menu))
+            .add_systems(OnExit(AppState::GameOver), pop_input_context(InputContext::Menu))
+            .add_systems(
+                OnEnter(AppState::Game),
+                (
+                    push_input_context(InputContext::Gameplay),
+                    push_input_context(InputContext::Paused).run_if(in_state(GameState::Paused)),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                (pop_input_context(InputContext::Paused), pop_input_context(InputContext::Gameplay)).chain(),
+            )
+            .add_systems(
+                OnEnter(GameState::Paused),
+                push_input_context(InputContext::Paused).run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                OnExit(GameState::Paused),
+                pop_input_context(InputContext::Paused).run_if(in_state(AppState::Game)),
+            )
             .add_systems(
                 Update,
-                handle_controls_state.run_if(in_state(GameState::Running)),
-            );
+                (
+                    emit_context_actions.run_if(is_live),
+                    replay_context_actions.run_if(is_replaying),
+                    close_dialogue,
+                    handle_gamepad_connections,
+                    (
+                        update_cursor_world_position,
+                        clear_blocked_mouse_target,
+                        handle_mouse_state.run_if(is_live),
+                        handle_controls_state.run_if(is_live),
+                        replay_actions.run_if(is_replaying),
+                        track_action_phases,
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::Running))
+                        .run_if(in_input_context(InputContext::Gameplay)),
+                )
+                    .chain(),
+            )
+            .add_systems(Last, (record_frame, save_recording_on_exit, advance_replay).chain());
     }
 }
 
-#[derive(Component, Debug)]
-pub struct Controls {
-    pub controls_map: HashMap<KeyCode, ControlledAction>,
-}
-
-#[derive(Resource, Debug, Default, Deref)]
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
 pub struct Actions {
     pub current_actions: HashSet<ControlledAction>,
 }
 
-#[derive(Event, Debug, Eq, PartialEq)]
+#[derive(Event, Debug, PartialEq)]
 pub struct ActionEvent {
-    pub actions: HashSet<ControlledAction>
+    pub actions: HashSet<ControlledAction>,
+    pub movement: Vec2,
+    pub aim: Option<Vec2>,
+}
+
+#[derive(Event, Debug, Eq, PartialEq)]
+pub struct ActionStartEvent {
+    pub action: ControlledAction,
+}
+
+#[derive(Event, Debug, Eq, PartialEq)]
+pub struct ActionHoldEvent {
+    pub action: ControlledAction,
+    pub held: Duration,
 }
 
 #[derive(Event, Debug, Eq, PartialEq)]
 pub struct ActionEndEvent {
-    pub action: ControlledAction
+    pub action: ControlledAction,
+    pub held: Duration,
+}
+
+#[derive(Event, Debug, Eq, PartialEq)]
+pub struct ActionDoubleTapEvent {
+    pub action: ControlledAction,
 }
 
 impl ActionEndEvent {
-    pub fn new(action: ControlledAction) -> Self {
+    pub fn new(action: ControlledAction, held: Duration) -> Self {
         Self {
-            action
+            action,
+            held,
         }
     }
 }
 
 impl ActionEvent {
-    pub fn new(actions: HashSet<ControlledAction>) -> Self {
+    pub fn new(actions: HashSet<ControlledAction>, movement: Vec2) -> Self {
         Self {
-            actions
+            actions,
+            movement,
+            aim: None,
         }
     }
 
+    pub fn with_aim(mut self, aim: Option<Vec2>) -> Self {
+        self.aim = aim;
+        self
+    }
+
     pub fn contains_running(&self) -> bool {
         self.actions.contains(&ControlledAction::Run)
     }
 
     pub fn contains_move(&self) -> bool {
-        self.actions
-            .iter()
-            .any(|action| action.is_move_action())
+        self.movement != Vec2::ZERO || self.actions.iter().any(|action| action.is_move_action())
     }
 
     pub fn contains_attack(&self) -> bool {
         self.actions.contains(&ControlledAction::Attack)
     }
 
+    pub fn contains_dash(&self) -> bool {
+        self.actions.contains(&ControlledAction::Dash)
+    }
+
+    pub fn contains_block(&self) -> bool {
+        self.actions.contains(&ControlledAction::Block)
+    }
+
     pub fn is_attack(&self) -> bool {
         self.actions.iter().all(|it| { it == &ControlledAction::Attack })
     }
 
     pub fn is_idle(&self) -> bool {
-        self.actions.iter().all(|it| { it == &ControlledAction::None })
+        self.movement == Vec2::ZERO && self.actions.iter().all(|it| { it == &ControlledAction::None })
     }
 }
 
-#[derive(Hash, Eq, Debug, Copy, Clone, Default, PartialEq)]
+#[derive(Hash, Eq, Debug, Copy, Clone, Default, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum ControlledAction {
     #[default]
     None,
@@ -91,6 +196,8 @@ pub enum ControlledAction {
     MoveRight,
     Run,
     Attack,
+    Dash,
+    Block,
 }
 
 impl Actions {
@@ -106,6 +213,17 @@ impl Actions {
 }
 
 impl ControlledAction {
+    pub const BINDABLE: [Self; 8] = [
+        ControlledAction::MoveUp,
+        ControlledAction::MoveLeft,
+        ControlledAction::MoveDown,
+        ControlledAction::MoveRight,
+        ControlledAction::Run,
+        ControlledAction::Attack,
+        ControlledAction::Dash,
+        ControlledAction::Block,
+    ];
+
     const MOVE_ACTIONS: [Self; 4] = [
         ControlledAction::MoveUp,
         ControlledAction::MoveDown,
@@ -121,6 +239,8 @@ impl ControlledAction {
             ControlledAction::MoveRight => Direction::Right,
             ControlledAction::Run => Direction::Zero,
             ControlledAction::Attack => Direction::Zero,
+            ControlledAction::Dash => Direction::Zero,
+            ControlledAction::Block => Direction::Zero,
             ControlledAction::None => Direction::Zero,
         }
     }
@@ -136,25 +256,43 @@ impl ControlledAction {
 
 pub fn handle_controls_state(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_devices: GamepadDevices,
+    mut mouse_state: ResMut<MouseState>,
     mut event_writer: EventWriter<ActionEvent>,
-    mut query: Query<&Controls>,
+    key_bindings: Res<KeyBindings>,
 ) {
     let pressed_keys: HashSet<KeyCode> = keyboard_input.get_pressed().cloned().collect();
-    let released_keys: HashSet<KeyCode> = keyboard_input.get_just_released().cloned().collect();
 
-    let controls = query.single_mut();
     let mut new_actions = HashSet::new();
+    let mut movement = Vec2::ZERO;
 
-    if !pressed_keys.is_empty() {
-        for pressed_key in pressed_keys.iter() {
-            if let Some(action) = controls.controls_map.get(pressed_key) {
-                new_actions.insert(*action);
-            }
+    for pressed_key in pressed_keys.iter() {
+        if let Some(action) = key_bindings.action_for(pressed_key) {
+            new_actions.insert(action);
         }
+    }
 
-        info!("Sending actions event: {:?}", &new_actions);
-        event_writer.send(ActionEvent::new(new_actions));
+    if let Some(gamepad_input) = gamepad_devices.read(&key_bindings) {
+        new_actions.extend(gamepad_input.actions);
+        movement = gamepad_input.movement;
+    }
+
+    if movement == Vec2::ZERO {
+        movement = Direction::vec_from_actions(&new_actions);
+    }
+
+    if movement == Vec2::ZERO {
+        movement = mouse_state.movement;
     } else {
-        event_writer.send(ActionEvent::new(HashSet::from([ControlledAction::None])));
+        mouse_state.target = None;
     }
+    new_actions.extend(mouse_state.actions.iter().copied());
+
+    if new_actions.is_empty() {
+        new_actions.insert(ControlledAction::None);
+    } else {
+        info!("Sending actions event: {:?}", &new_actions);
+    }
+
+    event_writer.send(ActionEvent::new(new_actions, movement).with_aim(mouse_state.aim));
 }
@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::{
+    info, warn, Axis, ButtonInput, EventReader, Gamepad, GamepadAxis, GamepadAxisType, GamepadButton,
+    GamepadButtonType, Gamepads, Res, ResMut, Resource, Vec2,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::game::controls::bindings::KeyBindings;
+use crate::game::controls::controls::ControlledAction;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq)]
+pub enum GamepadStick {
+    #[default]
+    Left,
+    Right,
+}
+
+impl GamepadStick {
+    pub fn axes(&self) -> (GamepadAxisType, GamepadAxisType) {
+        match self {
+            GamepadStick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            GamepadStick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GamepadBindings {
+    pub buttons: BTreeMap<ControlledAction, Vec<GamepadButtonType>>,
+    pub move_stick: GamepadStick,
+    pub dead_zone: f32,
+    pub press_threshold: f32,
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self {
+            buttons: BTreeMap::from([
+                (ControlledAction::MoveUp, vec![GamepadButtonType::DPadUp]),
+                (ControlledAction::MoveLeft, vec![GamepadButtonType::DPadLeft]),
+                (ControlledAction::MoveDown, vec![GamepadButtonType::DPadDown]),
+                (ControlledAction::MoveRight, vec![GamepadButtonType::DPadRight]),
+                (ControlledAction::Run, vec![GamepadButtonType::RightTrigger2]),
+                (ControlledAction::Attack, vec![GamepadButtonType::West]),
+                (ControlledAction::Dash, vec![GamepadButtonType::East]),
+                (ControlledAction::Block, vec![GamepadButtonType::LeftTrigger2]),
+            ]),
+            move_stick: GamepadStick::Left,
+            dead_zone: 0.2,
+            press_threshold: 0.5,
+        }
+    }
+}
+
+impl GamepadBindings {
+    pub fn action_for(&self, button: &GamepadButtonType) -> Option<ControlledAction> {
+        self.buttons
+            .iter()
+            .find(|(_, buttons)| buttons.contains(button))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn add_missing_defaults(&mut self, defaults: &GamepadBindings) {
+        for (action, buttons) in &defaults.buttons {
+            if self.buttons.contains_key(action) {
+                continue;
+            }
+
+            match buttons.iter().find_map(|button| self.action_for(button)) {
+                Some(bound) => warn!("Leaving {:?} unbound on gamepad, its buttons are used by {:?}", action, bound),
+                None => {
+                    self.buttons.insert(*action, buttons.clone());
+                }
+            }
+        }
+    }
+
+    pub fn apply_dead_zone(&self, stick: Vec2) -> Vec2 {
+        let length = stick.length();
+        if length <= self.dead_zone {
+            return Vec2::ZERO;
+        }
+
+        let scaled = ((length - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        stick / length * scaled
+    }
+
+    pub fn stick_actions(&self, stick: Vec2) -> HashSet<ControlledAction> {
+        let mut actions = HashSet::new();
+
+        if stick.y >= self.press_threshold {
+            actions.insert(ControlledAction::MoveUp);
+        }
+        if stick.y <= -self.press_threshold {
+            actions.insert(ControlledAction::MoveDown);
+        }
+        if stick.x >= self.press_threshold {
+            actions.insert(ControlledAction::MoveRight);
+        }
+        if stick.x <= -self.press_threshold {
+            actions.insert(ControlledAction::MoveLeft);
+        }
+
+        actions
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+pub struct GamepadInput {
+    pub actions: HashSet<ControlledAction>,
+    pub movement: Vec2,
+}
+
+pub fn read_gamepad(
+    gamepad: Gamepad,
+    bindings: &GamepadBindings,
+    buttons: &ButtonInput<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> GamepadInput {
+    let is_own = |button: &&GamepadButton| button.gamepad == gamepad;
+
+    let mut actions: HashSet<ControlledAction> = buttons
+        .get_pressed()
+        .filter(is_own)
+        .filter_map(|button| bindings.action_for(&button.button_type))
+        .collect();
+
+    let (x_axis, y_axis) = bindings.move_stick.axes();
+    let stick = Vec2::new(
+        axes.get(GamepadAxis::new(gamepad, x_axis)).unwrap_or_default(),
+        axes.get(GamepadAxis::new(gamepad, y_axis)).unwrap_or_default(),
+    );
+    let movement = bindings.apply_dead_zone(stick);
+    actions.extend(bindings.stick_actions(movement));
+
+    GamepadInput { actions, movement }
+}
+
+#[derive(SystemParam)]
+pub struct GamepadDevices<'w> {
+    active: Res<'w, ActiveGamepad>,
+    gamepads: Res<'w, Gamepads>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl GamepadDevices<'_> {
+    pub fn read(&self, key_bindings: &KeyBindings) -> Option<GamepadInput> {
+        let gamepad = self.active.0?;
+        let bindings = key_bindings.gamepad_for(self.gamepads.name(gamepad));
+
+        Some(read_gamepad(gamepad, bindings, &self.buttons, &self.axes))
+    }
+}
+
+pub fn handle_gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    mut active_gamepad: ResMut<ActiveGamepad>,
+    gamepads: Res<Gamepads>,
+) {
+    for connection_event in connection_events.read() {
+        match &connection_event.connection {
+            GamepadConnection::Connected(gamepad_info) => {
+                info!("Gamepad connected: {:?} ({})", connection_event.gamepad, gamepad_info.name);
+                if active_gamepad.0.is_none() {
+                    active_gamepad.0 = Some(connection_event.gamepad);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad disconnected: {:?}", connection_event.gamepad);
+                if active_gamepad.0 == Some(connection_event.gamepad) {
+                    active_gamepad.0 = gamepads
+                        .iter()
+                        .find(|gamepad| *gamepad != connection_event.gamepad);
+                    info!("Active gamepad: {:?}", active_gamepad.0);
+                }
+            }
+        }
+    }
+}
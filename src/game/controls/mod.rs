@@ -1 +1,7 @@
+pub mod bindings;
+pub mod buffer;
+pub mod context;
 pub mod controls;
+pub mod gamepad;
+pub mod mouse;
+pub mod recording;
@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+
+use bevy::prelude::{
+    ButtonInput, Camera, Component, EventReader, GlobalTransform, MouseButton, Query, Res, ResMut, Resource,
+    Transform, Vec2, Window, With,
+};
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+use crate::game::controls::bindings::KeyBindings;
+use crate::game::controls::controls::ControlledAction;
+use crate::game::movement::movement::MoveBlockedEvent;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MouseBindings {
+    pub attack: Option<MouseButton>,
+    pub move_to: Option<MouseButton>,
+    pub aim_with_cursor: bool,
+    pub click_to_move: bool,
+    pub arrive_radius: f32,
+}
+
+impl Default for MouseBindings {
+    fn default() -> Self {
+        Self {
+            attack: Some(MouseButton::Left),
+            move_to: Some(MouseButton::Right),
+            aim_with_cursor: true,
+            click_to_move: true,
+            arrive_radius: 8.0,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Controlled;
+
+#[derive(Resource, Debug, Default)]
+pub struct CursorWorldPosition(pub Option<Vec2>);
+
+#[derive(Resource, Debug, Default)]
+pub struct MouseState {
+    pub target: Option<Vec2>,
+    pub actions: HashSet<ControlledAction>,
+    pub movement: Vec2,
+    pub aim: Option<Vec2>,
+}
+
+pub fn update_cursor_world_position(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut cursor_world_position: ResMut<CursorWorldPosition>,
+) {
+    let (Ok(window), Ok((camera, camera_transform))) = (window_query.get_single(), camera_query.get_single()) else {
+        cursor_world_position.0 = None;
+        return;
+    };
+
+    cursor_world_position.0 = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor));
+}
+
+pub fn handle_mouse_state(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    cursor_world_position: Res<CursorWorldPosition>,
+    key_bindings: Res<KeyBindings>,
+    controlled_query: Query<&Transform, With<Controlled>>,
+    mut mouse_state: ResMut<MouseState>,
+) {
+    let bindings = &key_bindings.mouse;
+    mouse_state.actions.clear();
+    mouse_state.movement = Vec2::ZERO;
+    mouse_state.aim = None;
+
+    let Ok(transform) = controlled_query.get_single() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let cursor = cursor_world_position.0;
+
+    if bindings.aim_with_cursor {
+        mouse_state.aim = cursor
+            .map(|cursor| (cursor - position).normalize_or_zero())
+            .filter(|aim| *aim != Vec2::ZERO);
+    }
+
+    if bindings.attack.is_some_and(|button| mouse_input.pressed(button)) {
+        mouse_state.actions.insert(ControlledAction::Attack);
+    }
+
+    if bindings.click_to_move && bindings.move_to.is_some_and(|button| mouse_input.just_pressed(button)) {
+        mouse_state.target = cursor;
+    }
+
+    if let Some(target) = mouse_state.target {
+        let offset = target - position;
+        if offset.length() <= bindings.arrive_radius {
+            mouse_state.target = None;
+        } else {
+            mouse_state.movement = offset.normalize();
+        }
+    }
+}
+
+pub fn clear_blocked_mouse_target(
+    mut event_reader: EventReader<MoveBlockedEvent>,
+    controlled_query: Query<(), With<Controlled>>,
+    mut mouse_state: ResMut<MouseState>,
+) {
+    if event_reader.read().any(|blocked_event| controlled_query.contains(blocked_event.entity)) {
+        mouse_state.target = None;
+    }
+}
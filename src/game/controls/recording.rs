@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::{
+    info, warn, AppExit, EventReader, EventWriter, Res, ResMut, Resource, Time, Vec2,
+};
+use bevy::time::{Real, TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::game::controls::context::{ContextAction, ContextActionEvent, InputContext};
+use crate::game::controls::controls::{ActionEvent, ControlledAction};
+
+const RECORD_ARG: &str = "--record";
+const REPLAY_ARG: &str = "--replay";
+
+#[derive(Debug, Error)]
+pub enum InputRecordingError {
+    #[error("could not access input recording: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse input recording: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("could not serialize input recording: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    pub delta: Duration,
+    #[serde(default)]
+    pub context_actions: Vec<(InputContext, ContextAction)>,
+    #[serde(default)]
+    pub actions: Option<(HashSet<ControlledAction>, Vec2, Option<Vec2>)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    pub seed: u64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn load(path: &PathBuf) -> Result<Self, InputRecordingError> {
+        Ok(ron::de::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), InputRecordingError> {
+        fs::write(path, ron::ser::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Resource, Debug)]
+pub enum InputSession {
+    Live { seed: u64 },
+    Recording { path: PathBuf, recording: InputRecording },
+    Replaying { recording: InputRecording, frame: usize },
+}
+
+impl InputSession {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let path_after = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|index| args.get(index + 1))
+                .map(PathBuf::from)
+        };
+
+        if let Some(path) = path_after(REPLAY_ARG) {
+            match InputRecording::load(&path) {
+                Ok(recording) => {
+                    info!("Replaying {} input frames from: {:?}", recording.frames.len(), path);
+                    return Self::replay(recording);
+                }
+                Err(error) => warn!("Could not load input recording {:?}: {}", path, error),
+            }
+        }
+
+        let seed = rand::random();
+        match path_after(RECORD_ARG) {
+            Some(path) => {
+                info!("Recording input to: {:?}", path);
+                Self::Recording {
+                    path,
+                    recording: InputRecording {
+                        seed,
+                        frames: Vec::new(),
+                    },
+                }
+            }
+            None => Self::Live { seed },
+        }
+    }
+
+    pub fn replay(recording: InputRecording) -> Self {
+        Self::Replaying { recording, frame: 0 }
+    }
+
+    pub fn seed(&self) -> u64 {
+        match self {
+            InputSession::Live { seed } => *seed,
+            InputSession::Recording { recording, .. } | InputSession::Replaying { recording, .. } => recording.seed,
+        }
+    }
+
+    pub fn current_frame(&self) -> Option<&RecordedFrame> {
+        match self {
+            InputSession::Replaying { recording, frame } => recording.frames.get(*frame),
+            _ => None,
+        }
+    }
+
+    pub fn first_frame_strategy(&self) -> TimeUpdateStrategy {
+        match self.current_frame() {
+            Some(frame) => TimeUpdateStrategy::ManualDuration(frame.delta),
+            None => TimeUpdateStrategy::Automatic,
+        }
+    }
+}
+
+pub fn is_replaying(session: Res<InputSession>) -> bool {
+    matches!(*session, InputSession::Replaying { .. })
+}
+
+pub fn is_live(session: Res<InputSession>) -> bool {
+    !matches!(*session, InputSession::Replaying { .. })
+}
+
+pub fn replay_context_actions(session: Res<InputSession>, mut event_writer: EventWriter<ContextActionEvent>) {
+    let Some(frame) = session.current_frame() else {
+        return;
+    };
+
+    for (context, action) in frame.context_actions.iter() {
+        event_writer.send(ContextActionEvent {
+            context: *context,
+            action: *action,
+        });
+    }
+}
+
+pub fn replay_actions(session: Res<InputSession>, mut event_writer: EventWriter<ActionEvent>) {
+    if let Some((actions, movement, aim)) = session.current_frame().and_then(|frame| frame.actions.as_ref()) {
+        event_writer.send(ActionEvent::new(actions.clone(), *movement).with_aim(*aim));
+    }
+}
+
+pub fn record_frame(
+    mut session: ResMut<InputSession>,
+    mut context_reader: EventReader<ContextActionEvent>,
+    mut action_reader: EventReader<ActionEvent>,
+    time: Res<Time<Real>>,
+) {
+    let InputSession::Recording { recording, .. } = session.as_mut() else {
+        return;
+    };
+
+    recording.frames.push(RecordedFrame {
+        delta: time.delta(),
+        context_actions: context_reader.read().map(|event| (event.context, event.action)).collect(),
+        actions: action_reader
+            .read()
+            .last()
+            .map(|event| (event.actions.clone(), event.movement, event.aim)),
+    });
+}
+
+pub fn advance_replay(
+    mut session: ResMut<InputSession>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    mut exit_writer: EventWriter<AppExit>,
+) {
+    let InputSession::Replaying { recording, frame } = session.as_mut() else {
+        return;
+    };
+
+    *frame += 1;
+    match recording.frames.get(*frame) {
+        Some(next) => *time_update_strategy = TimeUpdateStrategy::ManualDuration(next.delta),
+        None => {
+            info!("Input replay finished after {} frames", frame);
+            exit_writer.send(AppExit::Success);
+        }
+    }
+}
+
+pub fn save_recording_on_exit(session: Res<InputSession>, mut exit_reader: EventReader<AppExit>) {
+    if exit_reader.read().next().is_none() {
+        return;
+    }
+
+    if let InputSession::Recording { path, recording } = session.as_ref() {
+        match recording.save(path) {
+            Ok(()) => info!("Saved {} input frames to: {:?}", recording.frames.len(), path),
+            Err(error) => warn!("Could not save input recording {:?}: {}", path, error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::InputPlugin;
+    use bevy::prelude::{
+        App, AppExtStates, EventReader, IntoSystemConfigs, MinimalPlugins, ResMut, Resource, State,
+        Update,
+    };
+    use bevy::state::app::StatesPlugin;
+    use rand::Rng;
+
+    use super::*;
+    use crate::game::controls::context::InputContextStack;
+    use crate::game::controls::controls::{ActionStartEvent, ControlsPlugin};
+    use crate::game::game::{toggle_pause, GameState};
+    use crate::game::movement::movement::MoveBlockedEvent;
+    use crate::game::random::random::GameRng;
+    use crate::systems::{transition_to_game_state, transition_to_main_menu_state};
+    use crate::AppState;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/start_and_move.ron");
+
+    #[derive(Resource, Default)]
+    struct StartedActions(Vec<ControlledAction>);
+
+    fn collect_started_actions(mut reader: EventReader<ActionStartEvent>, mut started: ResMut<StartedActions>) {
+        started.0.extend(reader.read().map(|event| event.action));
+    }
+
+    fn replay_headless(recording: InputRecording) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin))
+            .init_state::<AppState>()
+            .init_state::<GameState>()
+            .insert_resource(InputSession::replay(recording))
+            .add_event::<MoveBlockedEvent>()
+            .add_plugins(ControlsPlugin)
+            .init_resource::<StartedActions>()
+            .add_systems(
+                Update,
+                (
+                    (transition_to_game_state, transition_to_main_menu_state, toggle_pause)
+                        .after(replay_context_actions),
+                    collect_started_actions.after(replay_actions),
+                ),
+            );
+        app.finish();
+        app.cleanup();
+
+        while app.should_exit().is_none() {
+            app.update();
+        }
+
+        app
+    }
+
+    #[test]
+    fn replays_recording_headlessly() {
+        let recording = InputRecording::load(&PathBuf::from(FIXTURE)).unwrap();
+        let mut app = replay_headless(recording.clone());
+
+        assert_eq!(*app.world().resource::<State<AppState>>().get(), AppState::Game);
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Running);
+        assert_eq!(app.world().resource::<InputContextStack>().contexts, vec![InputContext::Gameplay]);
+        assert_eq!(app.world().resource::<StartedActions>().0, vec![ControlledAction::MoveRight]);
+
+        let mut expected = GameRng::seeded(recording.seed);
+        let mut replayed = app.world_mut().resource_mut::<GameRng>();
+        assert_eq!(replayed.0.gen::<u64>(), expected.0.gen::<u64>());
+    }
+}
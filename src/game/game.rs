@@ -1,10 +1,12 @@
 use bevy::prelude::{
-    default, in_state, info, App, AppExtStates, ButtonInput, Camera2dBundle, Commands,
-    IntoSystemConfigs, KeyCode, NextState, Plugin, Res, ResMut, Startup, State, States, Transform,
+    default, in_state, info, App, AppExtStates, Camera2dBundle, Commands, EventReader,
+    IntoSystemConfigs, NextState, Plugin, Res, ResMut, Startup, State, States, Transform,
     Update,
 };
 
 use crate::animation::animation::PepaAnimationPlugin;
+use crate::game::combat::combat::CombatPlugin;
+use crate::game::controls::context::{ContextAction, ContextActionEvent};
 use crate::game::controls::controls::ControlsPlugin;
 use crate::game::movement::movement::MovementPlugin;
 use crate::game::npc::npc::NpcPlugin;
@@ -24,21 +26,23 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
             .add_plugins((
+                PepaAnimationPlugin,
+                CombatPlugin,
                 PlayerPlugin,
                 MovementPlugin,
                 ControlsPlugin,
-                //NpcPlugin
+                NpcPlugin,
             ))
             .add_systems(Startup, spawn_camera)
             .add_systems(Update, (toggle_pause,).run_if(in_state(AppState::Game)));
     }
 }
 pub fn toggle_pause(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut context_events: EventReader<ContextActionEvent>,
     game_state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if context_events.read().any(|event| event.action == ContextAction::TogglePause) {
         match game_state.get() {
             GameState::Paused => {
                 next_state.set(GameState::Running);
@@ -1,7 +1,7 @@
 use crate::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
 
 use bevy::prelude::{
-    default, App, AssetServer, Commands, Component, Entity, OnEnter, Plugin, Query, Res,
+    default, App, AssetServer, Commands, Component, Entity, OnEnter, OnExit, Plugin, Query, Res,
     SpriteBundle, Transform, Vec3, With,
 };
 
@@ -11,7 +11,7 @@ impl Plugin for LocationPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(OnEnter(AppState::Game), spawn_station)
-            .add_systems(OnEnter(AppState::MainMenu), despawn_station_location);
+            .add_systems(OnExit(AppState::Game), despawn_station_location);
     }
 }
 
@@ -86,7 +86,7 @@ pub fn despawn_station_location(
     mut commands: Commands,
     location_query: Query<Entity, With<StationLocation>>,
 ) {
-    if let Ok(location_entity) = location_query.get_single() {
+    for location_entity in location_query.iter() {
         commands.entity(location_entity).despawn()
     }
 }
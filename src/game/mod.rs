@@ -1,6 +1,8 @@
+pub mod combat;
 pub mod controls;
 pub mod game;
 pub mod location;
 pub mod movement;
 pub mod npc;
 pub mod player;
+pub mod random;
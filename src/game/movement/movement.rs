@@ -1,24 +1,45 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use bevy::ecs::observer::TriggerTargets;
 use bevy::prelude::*;
-use bevy_rapier2d::prelude::KinematicCharacterController;
-use rand::{random, Rng};
+use bevy_rapier2d::prelude::{KinematicCharacterController, KinematicCharacterControllerOutput};
+use rand::Rng;
+use serde::Deserialize;
 
+use crate::animation::animation::{AnimationState, ClipChangeEvent};
 use crate::AppState;
-use crate::game::controls::controls::{Actions, ControlledAction};
+use crate::game::controls::controls::ControlledAction;
 use crate::game::game::GameState;
 
+type KnockbackTarget = (Option<&'static mut Knockback>, Option<&'static DashProfile>, Has<Dashing>);
+
 pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<MoveEvent>()
+            .add_event::<MoveStartEvent>()
+            .add_event::<MoveDirectionChangeEvent>()
             .add_event::<MoveEndEvent>()
+            .add_event::<MoveBlockedEvent>()
+            .add_event::<MoveCollisionEvent>()
+            .add_event::<DashEvent>()
+            .add_event::<KnockbackEvent>()
             .add_systems(
             Update,
-            (update_position)
+            (
+                start_dash,
+                update_dash,
+                apply_knockback,
+                update_knockback,
+                tick_dash_cooldown,
+                tick_invulnerability,
+                update_velocity,
+                update_position,
+                emit_move_collisions,
+                emit_move_lifecycle,
+            )
+                .chain()
                 .run_if(in_state(AppState::Game))
                 .run_if(in_state(GameState::Running)),
         );
@@ -28,9 +49,156 @@ impl Plugin for MovementPlugin {
 #[derive(Event, Debug)]
 pub struct MoveEvent {
     pub entity: Entity,
-    pub direction: Direction,
+    pub direction: Vec2,
+    pub running: bool,
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct MovementProfile {
+    pub max_speed: f32,
     pub acceleration: f32,
+    pub deceleration: f32,
+    pub run_multiplier: f32,
+}
+
+impl MovementProfile {
+    pub fn new(max_speed: f32, acceleration: f32, deceleration: f32, run_multiplier: f32) -> Self {
+        Self {
+            max_speed,
+            acceleration,
+            deceleration,
+            run_multiplier,
+        }
+    }
+
+    pub fn target_speed(&self, running: bool) -> f32 {
+        if running {
+            self.max_speed * self.run_multiplier
+        } else {
+            self.max_speed
+        }
+    }
+}
+
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq, Deref, DerefMut)]
+pub struct CharacterVelocity(pub Vec2);
+
+#[derive(Event, Debug)]
+pub struct DashEvent {
+    pub entity: Entity,
+    pub direction: Vec2,
+}
+
+impl DashEvent {
+    pub fn new(entity: &Entity, direction: Vec2) -> Self {
+        Self {
+            entity: *entity,
+            direction,
+        }
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct DashProfile {
+    pub distance: f32,
+    pub duration: f32,
+    pub cooldown: f32,
+    pub invulnerability: Option<f32>,
+}
+
+impl Default for DashProfile {
+    fn default() -> Self {
+        Self {
+            distance: 160.0,
+            duration: 0.2,
+            cooldown: 0.8,
+            invulnerability: Some(0.2),
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Dashing {
+    pub direction: Vec2,
     pub speed: f32,
+    pub timer: Timer,
+}
+
+#[derive(Component, Debug)]
+pub struct DashCooldown(pub Timer);
+
+#[derive(Event, Debug)]
+pub struct KnockbackEvent {
+    pub entity: Entity,
+    pub direction: Vec2,
+    pub strength: f32,
+    pub decay: f32,
+}
+
+impl KnockbackEvent {
+    pub fn new(entity: &Entity, direction: Vec2, strength: f32, decay: f32) -> Self {
+        Self {
+            entity: *entity,
+            direction,
+            strength,
+            decay,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Knockback {
+    pub velocity: Vec2,
+    pub timer: Timer,
+}
+
+impl Knockback {
+    pub fn current_velocity(&self) -> Vec2 {
+        self.velocity * self.timer.fraction_remaining()
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Invulnerable(pub Timer);
+
+impl Invulnerable {
+    pub fn from_seconds(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Facing(pub Direction);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self(Direction::Down)
+    }
+}
+
+#[derive(Component, Debug, Default, Copy, Clone, PartialEq)]
+pub struct MovementState {
+    pub direction: Direction,
+    pub blocked: bool,
+}
+
+impl MovementState {
+    pub fn is_moving(&self) -> bool {
+        self.direction != Direction::Zero
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct MoveStartEvent {
+    pub entity: Entity,
+    pub direction: Direction,
+}
+
+#[derive(Event, Debug)]
+pub struct MoveDirectionChangeEvent {
+    pub entity: Entity,
+    pub from: Direction,
+    pub to: Direction,
 }
 
 #[derive(Event, Debug)]
@@ -38,18 +206,44 @@ pub struct MoveEndEvent {
     pub entity: Entity,
 }
 
+#[derive(Event, Debug)]
+pub struct MoveBlockedEvent {
+    pub entity: Entity,
+    pub other: Entity,
+    pub direction: Direction,
+}
+
+#[derive(Event, Debug)]
+pub struct MoveCollisionEvent {
+    pub entity: Entity,
+    pub other: Entity,
+    pub normal: Vec2,
+    pub translation_remaining: Vec2,
+}
+
+const MOVING_SPEED_THRESHOLD: f32 = 1.0;
+const BLOCKED_NORMAL_THRESHOLD: f32 = 0.7;
+
+pub fn top_down_controller() -> KinematicCharacterController {
+    KinematicCharacterController {
+        slide: true,
+        autostep: None,
+        snap_to_ground: None,
+        ..default()
+    }
+}
+
 impl MoveEvent {
-    pub fn new(entity: &Entity, direction: Direction, acceleration: f32, speed: f32) -> Self {
+    pub fn new(entity: &Entity, direction: Vec2, running: bool) -> Self {
         Self {
             entity: *entity,
-            direction,
-            acceleration,
-            speed,
+            direction: direction.clamp_length_max(1.0),
+            running,
         }
     }
 }
 
-#[derive(Default, Copy, Clone, Debug, Eq, Hash)]
+#[derive(Default, Copy, Clone, Debug, Eq, Hash, Deserialize)]
 pub enum Direction {
     #[default]
     Zero,
@@ -101,119 +295,365 @@ impl Direction {
     ];
 
     pub fn from_actions(actions: HashSet<ControlledAction>) -> Direction {
-        if actions.contains(&ControlledAction::MoveUp) && actions.contains(&ControlledAction::MoveRight) {
-            return Direction::UpRight;
-        }
+        Self::from_vec(Self::vec_from_actions(&actions))
+    }
 
-        if actions.contains(&ControlledAction::MoveDown) && actions.contains(&ControlledAction::MoveLeft) {
-            return Direction::DownLeft;
-        }
+    pub fn vec_from_actions(actions: &HashSet<ControlledAction>) -> Vec2 {
+        actions
+            .iter()
+            .filter(|action| action.is_move_action())
+            .map(|action| action.get_direction().to_vec2())
+            .sum::<Vec2>()
+            .normalize_or_zero()
+    }
 
-        if actions.contains(&ControlledAction::MoveDown) && actions.contains(&ControlledAction::MoveRight) {
-            return Direction::DownRight;
+    pub fn from_vec(vector: Vec2) -> Direction {
+        if vector.length_squared() <= f32::EPSILON {
+            return Direction::Zero;
         }
 
-        if actions.contains(&ControlledAction::MoveUp) && actions.contains(&ControlledAction::MoveLeft) {
-            return Direction::UpLeft;
+        Self::DIRECTIONS
+            .iter()
+            .copied()
+            .max_by(|a, b| a.to_vec2().dot(vector).total_cmp(&b.to_vec2().dot(vector)))
+            .unwrap_or(Direction::Zero)
+    }
+
+    pub fn to_vec2(&self) -> Vec2 {
+        self.get_direction_vec().truncate().normalize_or_zero()
+    }
+
+    pub fn is_neg_x_axes(&self) -> bool {
+        Self::NEG_X_AXES.contains(self)
+    }
+
+    pub fn has_x_axis(&self) -> bool {
+        !matches!(self, Direction::Up | Direction::Down | Direction::Zero | Direction::Random)
+    }
+
+    pub fn right_facing(&self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::UpLeft => Direction::UpRight,
+            Direction::DownLeft => Direction::DownRight,
+            direction => *direction,
         }
+    }
 
-        if actions.contains(&ControlledAction::MoveUp) {
-            return Direction::Up;
+    pub fn get_direction_vec(&self) -> Vec3 {
+        match self {
+            Direction::Up => Vec3::Y,
+            Direction::Down => Vec3::NEG_Y,
+            Direction::Right => Vec3::X,
+            Direction::Left => Vec3::NEG_X,
+            Direction::UpRight => Vec3::new(1.0, 1.0, 0.0),
+            Direction::UpLeft => Vec3::new(-1.0, 1.0, 0.0),
+            Direction::DownRight => Vec3::new(1.0, -1.0, 0.0),
+            Direction::DownLeft => Vec3::new(-1.0, -1.0, 0.0),
+            Direction::Random => {
+                let mut rng = rand::thread_rng();
+                let generated =
+                    Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0).normalize();
+                info!("Generated: {:?}", generated);
+
+                generated
+            }
+            _ => Vec3::ZERO,
         }
+    }
+}
+
+pub fn update_velocity(
+    mut event_reader: EventReader<MoveEvent>,
+    mut query: Query<(Entity, &MovementProfile, &mut CharacterVelocity, Option<&mut Facing>)>,
+    dashing_query: Query<(), With<Dashing>>,
+    knockback_query: Query<(), With<Knockback>>,
+    time: Res<Time>,
+) {
+    let mut targets = HashMap::new();
 
-        if actions.contains(&ControlledAction::MoveDown) {
-            return Direction::Down;
+    for move_event in event_reader.read() {
+        if dashing_query.contains(move_event.entity) || knockback_query.contains(move_event.entity) {
+            continue;
         }
 
-        if actions.contains(&ControlledAction::MoveLeft) {
-            return Direction::Left;
+        let Ok((_, profile, _, facing)) = query.get_mut(move_event.entity) else {
+            continue;
+        };
+        info!("Get event: {:?}", &move_event);
+
+        let direction = Direction::from_vec(move_event.direction);
+        if let Some(mut facing) = facing {
+            if direction != Direction::Zero {
+                facing.0 = direction;
+            }
         }
 
-        if actions.contains(&ControlledAction::MoveRight) {
-            return Direction::Right;
+        targets.insert(move_event.entity, move_event.direction * profile.target_speed(move_event.running));
+    }
+
+    for (entity, profile, mut velocity, _) in query.iter_mut() {
+        if dashing_query.contains(entity) || knockback_query.contains(entity) {
+            continue;
         }
 
-        Direction::Zero
+        let target = targets.get(&entity).copied().unwrap_or(Vec2::ZERO);
+        let rate = if target == Vec2::ZERO {
+            profile.deceleration
+        } else {
+            profile.acceleration
+        };
+
+        let difference = target - velocity.0;
+        let step = rate * time.delta_seconds();
+
+        velocity.0 = if difference.length() <= step {
+            target
+        } else {
+            velocity.0 + difference.normalize() * step
+        };
     }
+}
 
-    pub fn vec_from_actions(actions: Vec<ControlledAction>) -> Vec3 {
-        if actions.contains(&ControlledAction::MoveUp) && actions.contains(&ControlledAction::MoveRight) {
-            return Direction::Up.get_direction_vec() + Direction::Right.get_direction_vec();
+pub fn start_dash(
+    mut commands: Commands,
+    mut event_reader: EventReader<DashEvent>,
+    mut query: Query<(&DashProfile, Option<&mut Facing>)>,
+    dashing_query: Query<(), With<Dashing>>,
+    cooldown_query: Query<(), With<DashCooldown>>,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
+) {
+    for dash_event in event_reader.read() {
+        if dashing_query.contains(dash_event.entity) || cooldown_query.contains(dash_event.entity) {
+            continue;
         }
 
-        if actions.contains(&ControlledAction::MoveDown) && actions.contains(&ControlledAction::MoveLeft) {
-            return Direction::Down.get_direction_vec() + Direction::Left.get_direction_vec();
+        let Ok((profile, facing)) = query.get_mut(dash_event.entity) else {
+            continue;
+        };
+
+        let vector = match (dash_event.direction.normalize_or_zero(), facing.as_deref()) {
+            (Vec2::ZERO, Some(facing)) => facing.0.to_vec2(),
+            (vector, _) => vector,
+        };
+
+        let direction = Direction::from_vec(vector);
+        if direction == Direction::Zero {
+            continue;
         }
 
-        if actions.contains(&ControlledAction::MoveDown) && actions.contains(&ControlledAction::MoveRight) {
-            return Direction::Down.get_direction_vec() + Direction::Right.get_direction_vec();
+        if let Some(mut facing) = facing {
+            facing.0 = direction;
         }
 
-        if actions.contains(&ControlledAction::MoveUp) && actions.contains(&ControlledAction::MoveLeft) {
-            return Direction::Up.get_direction_vec() + Direction::Left.get_direction_vec();
+        info!("Entity: {:?} dashes to: {:?}", dash_event.entity, vector);
+
+        let mut entity_commands = commands.entity(dash_event.entity);
+        entity_commands.insert(Dashing {
+            direction: vector,
+            speed: profile.distance / profile.duration,
+            timer: Timer::from_seconds(profile.duration, TimerMode::Once),
+        });
+
+        if let Some(invulnerability) = profile.invulnerability {
+            entity_commands.insert(Invulnerable::from_seconds(invulnerability));
         }
 
-        if actions.contains(&ControlledAction::MoveUp) {
-            return Direction::Up.get_direction_vec();
+        clip_event_writer.send(ClipChangeEvent::new(&dash_event.entity, AnimationState::Dash, direction));
+    }
+}
+
+pub fn update_dash(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Dashing, &mut CharacterVelocity, &DashProfile)>,
+    time: Res<Time>,
+) {
+    for (entity, mut dashing, mut velocity, profile) in query.iter_mut() {
+        if dashing.timer.finished() {
+            velocity.0 = Vec2::ZERO;
+            commands
+                .entity(entity)
+                .remove::<Dashing>()
+                .insert(DashCooldown(Timer::from_seconds(profile.cooldown, TimerMode::Once)));
+            continue;
         }
 
-        if actions.contains(&ControlledAction::MoveDown) {
-            return Direction::Down.get_direction_vec();
+        let remaining = dashing.timer.remaining_secs();
+        dashing.timer.tick(time.delta());
+
+        let delta = time.delta_seconds();
+        let portion = if delta > 0.0 { (remaining / delta).min(1.0) } else { 1.0 };
+        velocity.0 = dashing.direction * dashing.speed * portion;
+    }
+}
+
+pub fn apply_knockback(
+    mut commands: Commands,
+    mut event_reader: EventReader<KnockbackEvent>,
+    mut query: Query<KnockbackTarget, With<CharacterVelocity>>,
+) {
+    for knockback_event in event_reader.read() {
+        let Ok((knockback, dash_profile, dashing)) = query.get_mut(knockback_event.entity) else {
+            continue;
+        };
+
+        let impulse = knockback_event.direction.normalize_or_zero() * knockback_event.strength;
+        if impulse == Vec2::ZERO || knockback_event.decay <= 0.0 {
+            continue;
         }
 
-        if actions.contains(&ControlledAction::MoveLeft) {
-            return Direction::Left.get_direction_vec();
+        info!("Entity: {:?} knocked back with: {:?}", knockback_event.entity, impulse);
+
+        let velocity = match knockback {
+            Some(knockback) => knockback.current_velocity() + impulse,
+            None => impulse,
+        };
+
+        let mut entity_commands = commands.entity(knockback_event.entity);
+        entity_commands.insert(Knockback {
+            velocity,
+            timer: Timer::from_seconds(knockback_event.decay, TimerMode::Once),
+        });
+
+        if dashing {
+            entity_commands.remove::<Dashing>();
+            if let Some(profile) = dash_profile {
+                entity_commands.insert(DashCooldown(Timer::from_seconds(profile.cooldown, TimerMode::Once)));
+            }
         }
+    }
+}
 
-        if actions.contains(&ControlledAction::MoveRight) {
-            return Direction::Right.get_direction_vec();
+pub fn update_knockback(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Knockback, &mut CharacterVelocity)>,
+    time: Res<Time>,
+) {
+    for (entity, mut knockback, mut velocity) in query.iter_mut() {
+        knockback.timer.tick(time.delta());
+        velocity.0 = knockback.current_velocity();
+
+        if knockback.timer.finished() {
+            commands.entity(entity).remove::<Knockback>();
         }
+    }
+}
 
-        Direction::Zero.get_direction_vec()
+pub fn tick_dash_cooldown(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut DashCooldown)>,
+    time: Res<Time>,
+) {
+    for (entity, mut cooldown) in query.iter_mut() {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<DashCooldown>();
+        }
     }
+}
 
-    pub fn is_neg_x_axes(&self) -> bool {
-        Self::NEG_X_AXES.contains(self)
+pub fn tick_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
     }
+}
 
-    pub fn get_direction_vec(&self) -> Vec3 {
-        match self {
-            Direction::Up => Vec3::Y,
-            Direction::Down => Vec3::NEG_Y,
-            Direction::Right => Vec3::X,
-            Direction::Left => Vec3::NEG_X,
-            Direction::UpRight => Vec3::new(1.0, 1.0, 0.0),
-            Direction::UpLeft => Vec3::new(-1.0, 1.0, 0.0),
-            Direction::DownRight => Vec3::new(1.0, -1.0, 0.0),
-            Direction::DownLeft => Vec3::new(-1.0, -1.0, 0.0),
-            Direction::Random => {
-                let mut rng = rand::thread_rng();
-                let generated =
-                    Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0).normalize();
-                info!("Generated: {:?}", generated);
+pub fn update_position(
+    mut query: Query<(&CharacterVelocity, &mut Transform, Option<&mut KinematicCharacterController>)>,
+    time: Res<Time>,
+) {
+    for (velocity, mut transform, controller) in query.iter_mut() {
+        if velocity.0 == Vec2::ZERO {
+            continue;
+        }
 
-                generated
+        let translation = velocity.0 * time.delta_seconds();
+
+        match controller {
+            Some(mut controller) => {
+                controller.translation = Some(controller.translation.unwrap_or_default() + translation);
             }
-            _ => Vec3::ZERO,
+            None => transform.translation += translation.extend(0.0),
         }
     }
 }
 
-pub fn update_position(
-    mut event_reader: EventReader<MoveEvent>,
-    mut transform_query: Query<&mut Transform>,
-    time: Res<Time>,
+pub fn emit_move_collisions(
+    query: Query<(Entity, &KinematicCharacterControllerOutput), Changed<KinematicCharacterControllerOutput>>,
+    mut collision_event_writer: EventWriter<MoveCollisionEvent>,
 ) {
-    for mut move_event in event_reader.read() {
-        let (mut transform) = transform_query.get_mut(move_event.entity).unwrap();
-        info!("Get event: {:?}", &move_event);
+    for (entity, output) in query.iter() {
+        for collision in output.collisions.iter() {
+            let normal = collision.hit.details.map(|details| details.normal1).unwrap_or_default();
+            info!("Entity: {:?} collided with: {:?}", entity, collision.entity);
+
+            collision_event_writer.send(MoveCollisionEvent {
+                entity,
+                other: collision.entity,
+                normal,
+                translation_remaining: collision.translation_remaining,
+            });
+        }
+    }
+}
 
-        let mut direction = move_event.direction.get_direction_vec();
+pub fn emit_move_lifecycle(
+    mut query: Query<(Entity, &CharacterVelocity, &mut MovementState)>,
+    mut collision_event_reader: EventReader<MoveCollisionEvent>,
+    mut start_event_writer: EventWriter<MoveStartEvent>,
+    mut change_event_writer: EventWriter<MoveDirectionChangeEvent>,
+    mut end_event_writer: EventWriter<MoveEndEvent>,
+    mut blocked_event_writer: EventWriter<MoveBlockedEvent>,
+) {
+    let mut blockers = HashMap::new();
+    for collision_event in collision_event_reader.read() {
+        let Ok((_, velocity, _)) = query.get(collision_event.entity) else {
+            continue;
+        };
+
+        if velocity.normalize_or_zero().dot(collision_event.normal).abs() >= BLOCKED_NORMAL_THRESHOLD {
+            blockers.insert(collision_event.entity, collision_event.other);
+        }
+    }
+
+    for (entity, velocity, mut state) in query.iter_mut() {
+        let direction = if velocity.length() > MOVING_SPEED_THRESHOLD {
+            Direction::from_vec(velocity.0)
+        } else {
+            Direction::Zero
+        };
+
+        match (state.direction, direction) {
+            (from, to) if from == to => {}
+            (Direction::Zero, to) => {
+                info!("Entity: {:?} started moving to: {:?}", entity, to);
+                start_event_writer.send(MoveStartEvent { entity, direction: to });
+            }
+            (_, Direction::Zero) => {
+                info!("Entity: {:?} stopped moving", entity);
+                end_event_writer.send(MoveEndEvent { entity });
+            }
+            (from, to) => {
+                change_event_writer.send(MoveDirectionChangeEvent { entity, from, to });
+            }
+        }
 
-        if direction.length() > 0.0 {
-            direction = direction.normalize()
+        let blocker = blockers.get(&entity).filter(|_| direction != Direction::Zero);
+        if let (Some(other), false) = (blocker, state.blocked) {
+            info!("Entity: {:?} blocked by: {:?}", entity, other);
+            blocked_event_writer.send(MoveBlockedEvent {
+                entity,
+                other: *other,
+                direction,
+            });
         }
 
-        transform.translation += direction * move_event.speed * move_event.acceleration * time.delta_seconds();
+        state.direction = direction;
+        state.blocked = blocker.is_some();
     }
 }
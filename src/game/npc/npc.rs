@@ -1,18 +1,39 @@
 use bevy::prelude::{
-    default, in_state, info, App, AssetServer, Commands, Component, Entity, EventWriter,
+    default, in_state, info, App, Assets, Commands, Component, Entity, EventReader, EventWriter,
     IntoSystemConfigs, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, SpriteBundle, Time,
-    Timer, TimerMode, Transform, UVec2, Update, Vec3, With,
+    Timer, TimerMode, Transform, Update, Vec2, Vec3, With, Without,
 };
 use bevy_rapier2d::geometry::Collider;
-use bevy_rapier2d::prelude::{GravityScale, KinematicCharacterController, RigidBody};
-use rand::random;
+use bevy_rapier2d::prelude::RigidBody;
+use rand::Rng;
 
+use crate::animation::animation::{AnimatedCharacter, AnimationBundle, AnimationLibraries, AnimationLibrary, AnimationState, ClipChangeEvent};
+use crate::animation::state_machine::AnimationStateMachine;
+use crate::game::combat::combat::{Damage, DamageEvent, DamageKind, Dead, DespawnOnDeath, Health};
+use crate::game::combat::combo::{AttackEvent, Combo, ComboAttack, ComboChain};
+use crate::game::combat::hitbox::AttackProfile;
+use crate::game::combat::reaction::HitReaction;
+use crate::game::controls::mouse::Controlled;
 use crate::game::game::GameState;
-use crate::game::movement::movement::{Direction, MoveEvent};
+use crate::game::random::random::GameRng;
+use crate::game::movement::movement::{top_down_controller, CharacterVelocity, Direction, Facing, Knockback, KnockbackEvent, MoveBlockedEvent, MoveCollisionEvent, MoveEvent, MovementProfile, MovementState};
 use crate::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
 
 const WARRIOR_WIDTH: u32 = 128;
 const WARRIOR_HEIGHT: u32 = 128;
+const WARRIOR_SPEED: f32 = 50.0;
+const WARRIOR_ACCELERATION: f32 = 200.0;
+const WARRIOR_DECELERATION: f32 = 300.0;
+const WARRIOR_RUN_MULTIPLIER: f32 = 1.5;
+const WARRIOR_KNOCKBACK_STRENGTH: f32 = 400.0;
+const WARRIOR_KNOCKBACK_DECAY: f32 = 0.25;
+const WARRIOR_HEALTH: f32 = 60.0;
+const WARRIOR_CONTACT_DAMAGE: f32 = 10.0;
+const WARRIOR_ATTACK_RANGE: f32 = 120.0;
+const WARRIOR_COMBO_WINDOW: f32 = 0.6;
+const WARRIOR_IDLE_CHANCE: f64 = 1.0 / 9.0;
+
+type Pushable = (Without<Warrior>, Without<Knockback>, Without<Dead>);
 
 pub struct NpcPlugin;
 
@@ -26,8 +47,11 @@ impl Plugin for NpcPlugin {
             .add_systems(
                 Update,
                 (
-                    //warrior_movement,
-                    change_warrior_direction
+                    warrior_movement,
+                    change_warrior_direction,
+                    turn_blocked_warriors,
+                    push_back_from_warriors,
+                    warrior_attack,
                 )
                     .run_if(in_state(GameState::Running)),
             );
@@ -51,26 +75,68 @@ impl Default for WarriorDirectionTimer {
     }
 }
 
-pub fn spawn_warriors(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_warriors(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    animation_libraries: Res<AnimationLibraries>,
+    libraries: Res<Assets<AnimationLibrary>>,
+) {
+    let library_handle = animation_libraries.get(AnimatedCharacter::Fighter);
+    let animation_library = libraries.get(&library_handle).unwrap();
+    let (clip, resource) = animation_library.idle_clip(Direction::Zero).expect("loader checks for an Idle clip");
+    let combo_chain = warrior_combo();
+
     commands.spawn((
         SpriteBundle {
             transform: Transform::from_xyz(
-                WINDOW_WIDTH * random::<f32>(),
-                WINDOW_HEIGHT * random::<f32>(),
+                WINDOW_WIDTH * rng.gen::<f32>(),
+                WINDOW_HEIGHT * rng.gen::<f32>(),
                 1.0,
             )
             .with_scale(Vec3::new(1.5, 1.5, 0.0)),
-            texture: asset_server.load("sprites/characters/fighter/idle-walk.png"),
+            texture: resource.texture.clone(),
             ..default()
         },
+        AnimationBundle::new(library_handle, clip, resource).with_state_machine(AnimationStateMachine::combatant()),
+        Facing(Direction::Right),
+        MovementProfile::new(WARRIOR_SPEED, WARRIOR_ACCELERATION, WARRIOR_DECELERATION, WARRIOR_RUN_MULTIPLIER),
+        CharacterVelocity::default(),
+        MovementState::default(),
         Collider::cuboid((60 / 2) as f32, (55) as f32),
-        RigidBody::Dynamic,
-        GravityScale(0.0),
-        //KinematicCharacterController::default(),
+        RigidBody::KinematicPositionBased,
+        top_down_controller(),
+        Health::new(WARRIOR_HEALTH),
+        Damage::new(WARRIOR_CONTACT_DAMAGE, DamageKind::Contact),
+        (combo_chain.attacks[0].profile, combo_chain, Combo::default()),
+        HitReaction::default(),
+        DespawnOnDeath {},
         Warrior {},
     ));
 }
 
+fn warrior_combo() -> ComboChain {
+    ComboChain::new(
+        vec![
+            ComboAttack::new(
+                AnimationState::Attack,
+                AttackProfile::new(Damage::new(10.0, DamageKind::Melee), 70.0, Vec2::new(60.0, 100.0), 0.15),
+                0.3,
+            ),
+            ComboAttack::new(
+                AnimationState::Attack2,
+                AttackProfile::new(Damage::new(12.0, DamageKind::Melee), 75.0, Vec2::new(70.0, 100.0), 0.15),
+                0.4,
+            ),
+            ComboAttack::new(
+                AnimationState::Attack3,
+                AttackProfile::new(Damage::new(20.0, DamageKind::Melee), 85.0, Vec2::new(90.0, 110.0), 0.2),
+                1.0,
+            ),
+        ],
+        WARRIOR_COMBO_WINDOW,
+    )
+}
+
 pub fn despawn_warriors(mut commands: Commands, query: Query<Entity, With<Warrior>>) {
     if let Ok(entity) = query.get_single() {
         commands.entity(entity).despawn()
@@ -80,26 +146,110 @@ pub fn despawn_warriors(mut commands: Commands, query: Query<Entity, With<Warrio
 pub fn change_warrior_direction(
     mut warrior_direction: ResMut<WarriorDirection>,
     mut timer: ResMut<WarriorDirectionTimer>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time>,
 ) {
     timer.0.tick(time.delta());
 
     if timer.0.finished() {
-        warrior_direction.direction = Direction::Right;
+        warrior_direction.direction = if rng.gen_bool(WARRIOR_IDLE_CHANCE) {
+            Direction::Zero
+        } else {
+            Direction::DIRECTIONS[rng.gen_range(0..Direction::DIRECTIONS.len())]
+        };
         info!("New warrior direction: {:?}",warrior_direction.direction.get_direction_vec())
     }
 }
 
+pub fn turn_blocked_warriors(
+    mut warrior_direction: ResMut<WarriorDirection>,
+    mut event_reader: EventReader<MoveBlockedEvent>,
+    query: Query<Entity, With<Warrior>>,
+) {
+    for blocked_event in event_reader.read() {
+        if !query.contains(blocked_event.entity) {
+            continue;
+        }
+
+        warrior_direction.direction = Direction::from_vec(-blocked_event.direction.to_vec2());
+        info!("Warrior blocked, turning to: {:?}", warrior_direction.direction);
+    }
+}
+
+pub fn push_back_from_warriors(
+    mut collision_event_reader: EventReader<MoveCollisionEvent>,
+    mut knockback_event_writer: EventWriter<KnockbackEvent>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+    warrior_query: Query<(&Transform, &Damage), With<Warrior>>,
+    pushed_query: Query<&Transform, Pushable>,
+    dead_query: Query<(), With<Dead>>,
+) {
+    for collision_event in collision_event_reader.read() {
+        if dead_query.contains(collision_event.other) {
+            continue;
+        }
+
+        let (Ok((warrior_transform, damage)), Ok(pushed_transform)) = (
+            warrior_query.get(collision_event.other),
+            pushed_query.get(collision_event.entity),
+        ) else {
+            continue;
+        };
+
+        let direction = (pushed_transform.translation - warrior_transform.translation).truncate();
+        knockback_event_writer.send(KnockbackEvent::new(
+            &collision_event.entity,
+            direction,
+            WARRIOR_KNOCKBACK_STRENGTH,
+            WARRIOR_KNOCKBACK_DECAY,
+        ));
+        damage_event_writer.send(DamageEvent::new(Some(collision_event.other), &collision_event.entity, damage));
+    }
+}
+
+pub fn warrior_attack(
+    warrior_query: Query<(Entity, &Transform), With<Warrior>>,
+    target_query: Query<(Entity, &Transform), With<Controlled>>,
+    dead_query: Query<(), With<Dead>>,
+    mut attack_event_writer: EventWriter<AttackEvent>,
+) {
+    let Ok((target, target_transform)) = target_query.get_single() else {
+        return;
+    };
+
+    if dead_query.contains(target) {
+        return;
+    }
+
+    for (entity, transform) in warrior_query.iter() {
+        if dead_query.contains(entity) {
+            continue;
+        }
+
+        let direction = (target_transform.translation - transform.translation).truncate();
+        if direction.length() <= WARRIOR_ATTACK_RANGE {
+            attack_event_writer.send(AttackEvent::new(&entity, direction.normalize_or_zero()));
+        }
+    }
+}
+
 pub fn warrior_movement(
-    mut query: Query<Entity, With<Warrior>>,
+    query: Query<(Entity, &Facing), With<Warrior>>,
     mut move_event_writer: EventWriter<MoveEvent>,
+    mut clip_event_writer: EventWriter<ClipChangeEvent>,
     warrior_direction: Res<WarriorDirection>,
 ) {
     info!(
         "Warrior direction from res: {:?}",
         warrior_direction.direction
     );
-    for entity in query.iter() {
-        move_event_writer.send(MoveEvent::new(&entity, warrior_direction.direction, 50.0, 1.0));
+    for (entity, facing) in query.iter() {
+        if warrior_direction.direction == Direction::Zero {
+            clip_event_writer.send(ClipChangeEvent::new(&entity, AnimationState::Idle, facing.0));
+            continue;
+        }
+
+        move_event_writer.send(MoveEvent::new(&entity, warrior_direction.direction.to_vec2(), false));
+        clip_event_writer.send(ClipChangeEvent::new(&entity, AnimationState::Walk, warrior_direction.direction));
     }
 }
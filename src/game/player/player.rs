@@ -1,35 +1,38 @@
-use std::cmp::PartialEq;
-use std::collections::{HashMap, HashSet};
-use std::time::Duration;
-use std::vec;
-
-use bevy::asset::ErasedAssetLoader;
-use bevy::audio::CpalSample;
+use bevy::color::Color;
 use bevy::prelude::{
-    App, AssetEvent, Assets, AssetServer, ButtonInput, Camera, Commands, Component, default, Entity,
-    EventReader, EventWriter, Handle, Image, in_state, info, IntoSystemConfigs, KeyCode,
-    NextState, OnEnter, OnExit, Plugin, Query, Res, ResMut, Resource, Sprite, SpriteBundle,
-    TextureAtlas, TextureAtlasBuilder, TextureAtlasLayout, Time, Timer, TimerMode, Transform,
-    TransformBundle, Update, UVec2, Vec3, With, Without,
-};
-use bevy::prelude::KeyCode::{
-    ArrowDown, ArrowLeft, ArrowRight, ArrowUp, KeyA, KeyD, KeyF, KeyS, KeyW, ShiftLeft, ShiftRight,
+    App, Assets, AssetServer, AudioBundle, Camera, Commands, Component, default, Entity,
+    EventReader, EventWriter, in_state, info, IntoSystemConfigs,
+    NextState, OnEnter, OnExit, PlaybackSettings, Plugin, Query, Res, ResMut, Sprite, SpriteBundle,
+    Time, Timer, TimerMode, Transform, Update, Vec2, Vec3, With, Without,
 };
-use bevy_rapier2d::dynamics::GravityScale;
 use bevy_rapier2d::prelude::{
-    Collider, ImpulseJoint, KinematicCharacterController, NoUserData, RapierDebugRenderPlugin,
-    RapierPhysicsPlugin, RigidBody,
+    Collider, NoUserData, RapierDebugRenderPlugin, RapierPhysicsPlugin, RigidBody,
 };
 
 use crate::{AppState, WINDOW_HEIGHT, WINDOW_WIDTH};
-use crate::animation::animation::{animate_clip, AnimationClip, AnimationClipResource, AnimationIndices, AnimationLibrary, AnimationResource, AnimationState, change_animation_clip, ClipChangeEvent, PepaAnimationPlugin};
-use crate::game::controls::controls::{ActionEndEvent, ActionEvent, Actions, ControlledAction, Controls};
+use crate::animation::animation::{AnimatedCharacter, AnimationBundle, AnimationLibraries, AnimationLibrary, AnimationMarkerEvent, AnimationState, ClipChangeEvent, FOOTSTEP_MARKER};
+use crate::animation::state_machine::AnimationStateMachine;
+use crate::game::combat::combat::{Damage, DamageKind, DeathEvent, Health};
+use crate::game::combat::block::{BlockEvent, BlockProfile};
+use crate::game::combat::combo::{AttackEvent, Combo, ComboAttack, ComboChain};
+use crate::game::combat::hitbox::AttackProfile;
+use crate::game::combat::reaction::HitReaction;
+use crate::game::controls::buffer::{track_action_phases, InputBuffer};
+use crate::game::controls::controls::{ActionEndEvent, ActionEvent, ActionHoldEvent, ActionStartEvent, ControlledAction};
+use crate::game::controls::mouse::Controlled;
 use crate::game::game::GameState;
-use crate::game::movement::movement::{Direction, MoveEndEvent, MoveEvent};
+use crate::game::movement::movement::{CharacterVelocity, DashEvent, DashProfile, Direction, Facing, MoveEvent, MovementProfile, MovementState, top_down_controller};
 
 const STARTING_TRANSLATION: Vec3 = Vec3::new(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0, 1.0);
 const PLAYER_SPEED: f32 = 200.0;
+const PLAYER_ACCELERATION: f32 = 1600.0;
+const PLAYER_DECELERATION: f32 = 2000.0;
+const PLAYER_RUN_MULTIPLIER: f32 = 2.0;
 const PLAYER_SIZE: f32 = 64.0;
+const PLAYER_HEALTH: f32 = 100.0;
+const PLAYER_GAME_OVER_DELAY: f32 = 1.5;
+const PLAYER_DEAD_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+const PLAYER_COMBO_WINDOW: f32 = 0.5;
 
 const RAW_PLAYER_INITIAL_WIDTH: u32 = 52;
 const RAW_PLAYER_INITIAL_HEIGHT: u32 = 52;
@@ -39,28 +42,20 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
-            //TODO: move setup_animations to animation plugin
-            .add_event::<ClipChangeEvent>()
-            .init_resource::<AnimationLibrary>()
-            .init_resource::<PlayerAsset>()
-            .init_resource::<PlayerAssets>()
-            .add_systems(OnEnter(AppState::Loading), load_player_assets)
-            .add_systems(
-                Update,
-                check_assets_loading.run_if(in_state(AppState::Loading)),
-            )
             .add_systems(OnEnter(AppState::Game), (spawn_player,).chain())
             .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
             .add_plugins(RapierDebugRenderPlugin::default())
-            .add_systems(OnEnter(AppState::MainMenu), despawn_player)
+            .add_systems(OnExit(AppState::Game), despawn_player)
             .add_systems(
                 Update,
                 (
                     player_movement,
                     stick_camera_to_player,
-                    //TODO: move animate to animation plugin
-                    animate_clip,
-                    change_animation_clip
+                    play_footstep_sound,
+                    player_attack.after(player_movement).after(track_action_phases),
+                    player_block.after(player_movement),
+                    start_game_over_timer,
+                    game_over_on_player_death.after(start_game_over_timer),
                 )
                     .run_if(in_state(AppState::Game))
                     .run_if(in_state(GameState::Running)),
@@ -71,210 +66,138 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct Player;
 
-#[derive(Resource, Debug, Default, Eq, PartialEq, Hash)]
-pub struct PlayerAsset {
-    pub texture: Handle<Image>,
-    pub is_loaded: bool,
-}
-
-#[derive(Resource, Default, Debug, Eq, PartialEq)]
-pub struct PlayerAssets {
-    pub assets: Vec<PlayerAsset>,
-}
+#[derive(Component)]
+pub struct GameOverTimer(Timer);
 
-pub fn load_player_assets(
+pub fn spawn_player(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
-) {
-    info!("Loading Player assets");
-
-    let idle_texture = asset_server.load("sprites/characters/raw_player/idle-sheet.png");
-    let move_texture = asset_server.load("sprites/characters/raw_player/move.png");
-    let attack_texture = asset_server.load("sprites/characters/raw_player/attack.png");
-    let idle_atlas = layouts.add(create_atlas(4, 2));
-    let move_atlas = layouts.add(create_atlas(4, 8));
-    let run_atlas = layouts.add(create_atlas(4, 8));
-    let attack_atlas = layouts.add(create_atlas(4, 8));
-
-    let create_resource = |indices: AnimationIndices, texture: Handle<Image>, atlas: Handle<TextureAtlasLayout>, timer_mills: u64| {
-        (
-            AnimationClipResource::new(indices, timer_mills, TimerMode::Repeating),
-            AnimationResource::new(texture.clone(), move_atlas.clone()),
-        )
-    };
-
-    let library = AnimationLibrary {
-        clips: HashMap::from([
-            //Idle
-            ((AnimationState::Idle, Direction::Zero), create_resource(AnimationIndices::new(0, 6), idle_texture.clone(), idle_atlas.clone(), 125)),
-            //Walk
-            ((AnimationState::Walk, Direction::Down), create_resource(AnimationIndices::new(0, 3), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::DownRight), create_resource(AnimationIndices::new(4, 7), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::Right), create_resource(AnimationIndices::new(8, 11), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::UpRight), create_resource(AnimationIndices::new(12, 15), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::Up), create_resource(AnimationIndices::new(16, 19), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::UpLeft), create_resource(AnimationIndices::new(20, 23), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::Left), create_resource(AnimationIndices::new(24, 27), move_texture.clone(), move_atlas.clone(), 125)),
-            ((AnimationState::Walk, Direction::DownLeft), create_resource(AnimationIndices::new(28, 31), move_texture.clone(), move_atlas.clone(), 125)),
-            //Run
-            ((AnimationState::Run, Direction::Down), create_resource(AnimationIndices::new(0, 3), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::DownRight), create_resource(AnimationIndices::new(4, 7), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::Right), create_resource(AnimationIndices::new(8, 11), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::UpRight), create_resource(AnimationIndices::new(12, 15), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::Up), create_resource(AnimationIndices::new(16, 19), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::UpLeft), create_resource(AnimationIndices::new(20, 23), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::Left), create_resource(AnimationIndices::new(24, 27), move_texture.clone(), run_atlas.clone(), 125)),
-            ((AnimationState::Run, Direction::DownLeft), create_resource(AnimationIndices::new(28, 31), move_texture.clone(), run_atlas.clone(), 125)),
-            //Attack
-            ((AnimationState::Attack, Direction::Zero), create_resource(AnimationIndices::new(0, 3), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::Down), create_resource(AnimationIndices::new(0, 3), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::DownRight), create_resource(AnimationIndices::new(4, 7), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::Right), create_resource(AnimationIndices::new(8, 11), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::UpRight), create_resource(AnimationIndices::new(12, 15), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::Up), create_resource(AnimationIndices::new(16, 19), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::UpLeft), create_resource(AnimationIndices::new(20, 23), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::Left), create_resource(AnimationIndices::new(24, 27), attack_texture.clone(), attack_atlas.clone(), 200)),
-            ((AnimationState::Attack, Direction::DownLeft), create_resource(AnimationIndices::new(28, 31), attack_texture.clone(), attack_atlas.clone(), 200)),
-        ]),
-    };
-
-    commands.insert_resource(PlayerAssets {
-        assets: vec![
-            PlayerAsset {
-                texture: idle_texture,
-                is_loaded: false,
-            },
-            PlayerAsset {
-                texture: move_texture,
-                is_loaded: false,
-            },
-        ]
-    });
-    commands.insert_resource(library);
-}
-
-fn create_atlas(cols: u32, rows: u32) -> TextureAtlasLayout {
-    TextureAtlasLayout::from_grid(
-        UVec2::new(RAW_PLAYER_INITIAL_WIDTH, RAW_PLAYER_INITIAL_HEIGHT),
-        cols,
-        rows,
-        None,
-        None,
-    )
-}
-
-pub fn check_assets_loading(
-    mut assets_event: EventReader<AssetEvent<Image>>,
-    mut next_state: ResMut<NextState<AppState>>,
-    mut player_animation_assets: ResMut<PlayerAssets>,
+    animation_libraries: Res<AnimationLibraries>,
+    libraries: Res<Assets<AnimationLibrary>>,
 ) {
-    for event in assets_event.read() {
-        for mut asset in player_animation_assets.assets.iter_mut() {
-            if event.is_loaded_with_dependencies(asset.texture.id()) {
-                asset.is_loaded = true
-            }
-        }
-    }
-
-    if player_animation_assets.assets.iter().all(|asset| asset.is_loaded) {
-        info!("Assets has been loaded");
-        next_state.set(AppState::MainMenu)
-    }
-}
-
-pub fn spawn_player(mut commands: Commands, animation_library: Res<AnimationLibrary>) {
     info!("Spawning Player");
-    let (clip, resource) = animation_library.clips.get(&(AnimationState::Idle, Direction::Zero)).unwrap();
+    let library_handle = animation_libraries.get(AnimatedCharacter::RawPlayer);
+    let animation_library = libraries.get(&library_handle).unwrap();
+    let (clip, resource) = animation_library.idle_clip(Facing::default().0).expect("loader checks for an Idle clip");
+    let combo_chain = player_combo();
     commands.spawn((
         SpriteBundle {
             transform: Transform::from_translation(STARTING_TRANSLATION).with_scale(Vec3::new(5.0, 5.0, 1.0)),
             texture: resource.texture.clone(),
             ..default()
         },
-        AnimationClip::new_with_timer(clip.indices.clone(), clip.timer.clone()),
-        TextureAtlas {
-            layout: resource.atlas_layout.clone(),
-            index: clip.indices.first,
-        },
-        Controls {
-            //TODO: move to resources
-            controls_map: HashMap::from([
-                (KeyW, ControlledAction::MoveUp),
-                (KeyA, ControlledAction::MoveLeft),
-                (KeyS, ControlledAction::MoveDown),
-                (KeyD, ControlledAction::MoveRight),
-                (ArrowUp, ControlledAction::MoveUp),
-                (ArrowLeft, ControlledAction::MoveLeft),
-                (ArrowDown, ControlledAction::MoveDown),
-                (ArrowRight, ControlledAction::MoveRight),
-                (ShiftLeft, ControlledAction::Run),
-                (KeyF, ControlledAction::Attack),
-            ]),
-        },
+        AnimationBundle::new(library_handle, clip, resource).with_state_machine(AnimationStateMachine::combatant()),
+        Facing::default(),
+        MovementProfile::new(PLAYER_SPEED, PLAYER_ACCELERATION, PLAYER_DECELERATION, PLAYER_RUN_MULTIPLIER),
+        CharacterVelocity::default(),
+        MovementState::default(),
+        DashProfile::default(),
         Collider::cuboid(
             (RAW_PLAYER_INITIAL_WIDTH / 4) as f32,
             (RAW_PLAYER_INITIAL_HEIGHT / 4) as f32,
         ),
         RigidBody::KinematicPositionBased,
+        top_down_controller(),
+        Health::new(PLAYER_HEALTH),
+        (combo_chain.attacks[0].profile, combo_chain, Combo::default(), BlockProfile::default()),
+        HitReaction::default(),
+        Controlled {},
         Player {},
     ));
 }
 
+fn player_combo() -> ComboChain {
+    ComboChain::new(
+        vec![
+            ComboAttack::new(
+                AnimationState::Attack,
+                AttackProfile::new(Damage::new(20.0, DamageKind::Melee), 80.0, Vec2::new(70.0, 110.0), 0.2),
+                0.1,
+            ),
+            ComboAttack::new(
+                AnimationState::Attack2,
+                AttackProfile::new(Damage::new(25.0, DamageKind::Melee), 90.0, Vec2::new(80.0, 120.0), 0.15),
+                0.15,
+            ),
+            ComboAttack::new(
+                AnimationState::Attack3,
+                AttackProfile::new(Damage::new(40.0, DamageKind::Melee), 110.0, Vec2::new(100.0, 140.0), 0.25),
+                0.4,
+            ),
+        ],
+        PLAYER_COMBO_WINDOW,
+    )
+}
+
 pub fn player_movement(
-    mut query: Query<Entity, With<Player>>,
+    mut query: Query<(Entity, &mut Facing), With<Player>>,
     mut event_reader: EventReader<ActionEvent>,
     mut move_event_writer: EventWriter<MoveEvent>,
+    mut dash_event_writer: EventWriter<DashEvent>,
     mut clip_event_writer: EventWriter<ClipChangeEvent>,
 ) {
     let mut prev_event = None;
     for event in event_reader.read() {
-        let player_entity = query.single();
+        let (player_entity, mut facing) = query.single_mut();
         info!("Get event: {:?}", event);
 
         if prev_event == Some(event) {
             return;
         }
 
+        let aim = event.aim.map(Direction::from_vec);
+
+        if event.contains_dash() && !event.contains_block() {
+            dash_event_writer.send(DashEvent::new(&player_entity, event.movement));
+        }
+
         if event.is_idle() {
-            clip_event_writer.send(ClipChangeEvent::new(&player_entity, AnimationState::Idle, Direction::Zero));
+            if let Some(aim) = aim {
+                facing.0 = aim;
+            }
+            clip_event_writer.send(ClipChangeEvent::new(&player_entity, AnimationState::Idle, facing.0));
             prev_event = Some(event);
             return;
         }
 
         if event.is_attack() {
-            let attack_event = ClipChangeEvent::new(&player_entity, AnimationState::Attack, Direction::Zero);
-            let move_event = MoveEvent::new(&player_entity, Direction::Zero, 1.0, PLAYER_SPEED);
-            info!("Sending Move event: {:?} and ClipChange event: {:?}", &move_event, &attack_event);
+            if let Some(aim) = aim {
+                facing.0 = aim;
+            }
+            let move_event = MoveEvent::new(&player_entity, Vec2::ZERO, false);
+            info!("Sending Move event: {:?}", &move_event);
 
             move_event_writer.send(move_event);
-            clip_event_writer.send(attack_event);
             prev_event = Some(event);
             return;
         }
 
         if event.contains_move() {
-            let direction = Direction::from_actions(event.actions.clone());
+            let direction = event.movement;
 
             if event.contains_attack() {
-                let attack_event = ClipChangeEvent::new(&player_entity, AnimationState::Attack, direction);
-                let move_event = MoveEvent::new(&player_entity, direction, 1.0, PLAYER_SPEED);
-                info!("Sending Move event: {:?} and ClipChange event: {:?}", &move_event, &attack_event);
+                let move_event = MoveEvent::new(&player_entity, direction, false);
+                info!("Sending Move event: {:?}", &move_event);
 
                 move_event_writer.send(move_event);
-                clip_event_writer.send(attack_event);
                 prev_event = Some(event);
                 return;
             }
 
-            let (animation_state, speed_multiplier) = if event.contains_running() {
-                (AnimationState::Run, 2.0)
-            } else {
-                (AnimationState::Run, 1.0)
-            };
+            if event.contains_block() {
+                let clip_event = ClipChangeEvent::towards(&player_entity, AnimationState::Shield, direction);
+                let move_event = MoveEvent::new(&player_entity, direction, false);
+                info!("Sending Move event: {:?} and ClipChange event: {:?}", &move_event, &clip_event);
 
-            let clip_event = ClipChangeEvent::new(&player_entity, animation_state, direction);
-            let move_event = MoveEvent::new(&player_entity, direction, speed_multiplier, PLAYER_SPEED);
+                move_event_writer.send(move_event);
+                clip_event_writer.send(clip_event);
+                prev_event = Some(event);
+                return;
+            }
+
+            let animation_state = if event.contains_running() { AnimationState::Run } else { AnimationState::Walk };
+
+            let clip_event = ClipChangeEvent::towards(&player_entity, animation_state, direction);
+            let move_event = MoveEvent::new(&player_entity, direction, event.contains_running());
 
             info!("Sending Move event: {:?} and ClipChange event: {:?}", &move_event, &clip_event);
             move_event_writer.send(move_event);
@@ -285,6 +208,109 @@ pub fn player_movement(
     }
 }
 
+pub fn player_attack(
+    query: Query<(Entity, &Facing, &Combo), With<Player>>,
+    mut action_reader: EventReader<ActionEvent>,
+    mut input_buffer: ResMut<InputBuffer>,
+    mut attack_event_writer: EventWriter<AttackEvent>,
+    time: Res<Time>,
+) {
+    let action_event = action_reader.read().last();
+    let Ok((player_entity, facing, combo)) = query.get_single() else {
+        return;
+    };
+
+    if action_event.is_some_and(|event| event.contains_block()) {
+        return;
+    }
+
+    // Presses made during a swing wait for the combo, their buffer window starts once it is ready.
+    if combo.is_busy() {
+        input_buffer.defer(ControlledAction::Attack, time.elapsed());
+        return;
+    }
+
+    if !input_buffer.consume(ControlledAction::Attack) {
+        return;
+    }
+
+    let direction = action_event
+        .and_then(|event| event.aim.or((event.movement != Vec2::ZERO).then_some(event.movement)))
+        .unwrap_or(facing.0.to_vec2());
+    attack_event_writer.send(AttackEvent::new(&player_entity, direction));
+}
+
+pub fn player_block(
+    query: Query<Entity, With<Player>>,
+    mut start_reader: EventReader<ActionStartEvent>,
+    mut hold_reader: EventReader<ActionHoldEvent>,
+    mut end_reader: EventReader<ActionEndEvent>,
+    mut block_event_writer: EventWriter<BlockEvent>,
+) {
+    let Ok(player_entity) = query.get_single() else {
+        return;
+    };
+
+    let started = start_reader.read().any(|event| event.action == ControlledAction::Block);
+    let held = hold_reader.read().any(|event| event.action == ControlledAction::Block);
+    if started || held {
+        block_event_writer.send(BlockEvent::raise(&player_entity));
+    }
+
+    if end_reader.read().any(|event| event.action == ControlledAction::Block) {
+        block_event_writer.send(BlockEvent::lower(&player_entity));
+    }
+}
+
+pub fn start_game_over_timer(
+    mut commands: Commands,
+    mut player_query: Query<&mut Sprite, With<Player>>,
+    mut death_reader: EventReader<DeathEvent>,
+) {
+    for death_event in death_reader.read() {
+        let Ok(mut sprite) = player_query.get_mut(death_event.entity) else {
+            continue;
+        };
+
+        info!("Player died, game over in {}s", PLAYER_GAME_OVER_DELAY);
+        sprite.color = PLAYER_DEAD_COLOR;
+        commands
+            .entity(death_event.entity)
+            .insert(GameOverTimer(Timer::from_seconds(PLAYER_GAME_OVER_DELAY, TimerMode::Once)));
+    }
+}
+
+pub fn game_over_on_player_death(
+    mut player_query: Query<&mut GameOverTimer, With<Player>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    time: Res<Time>,
+) {
+    if let Ok(mut timer) = player_query.get_single_mut() {
+        if timer.0.tick(time.delta()).just_finished() {
+            info!("Game over");
+            next_state.set(AppState::GameOver);
+        }
+    }
+}
+
+pub fn play_footstep_sound(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    player_query: Query<Entity, With<Player>>,
+    mut marker_reader: EventReader<AnimationMarkerEvent>,
+) {
+    for event in marker_reader.read() {
+        if event.marker != FOOTSTEP_MARKER || !player_query.contains(event.entity) {
+            continue;
+        }
+
+        commands.spawn(AudioBundle {
+            source: asset_server.load("audio/walk-sound.wav"),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
+
 pub fn stick_camera_to_player(
     mut camera_query: Query<&mut Transform, With<Camera>>,
     player_query: Query<&Transform, (With<Player>, Without<Camera>)>,
@@ -303,3 +329,95 @@ pub fn despawn_player(mut commands: Commands, player_query: Query<Entity, With<P
         commands.entity(player_entity).despawn()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::{MinimalPlugins, Resource};
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::animation::state_machine::AnimationFinishedEvent;
+    use crate::game::combat::combat::HurtEvent;
+    use crate::game::combat::combo::advance_combos;
+    use crate::game::controls::buffer::ActionTimings;
+    use crate::game::controls::controls::{ActionDoubleTapEvent, Actions};
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn combo_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+            .init_resource::<Actions>()
+            .init_resource::<ActionTimings>()
+            .init_resource::<InputBuffer>()
+            .add_event::<ActionEvent>()
+            .add_event::<ActionStartEvent>()
+            .add_event::<ActionHoldEvent>()
+            .add_event::<ActionEndEvent>()
+            .add_event::<ActionDoubleTapEvent>()
+            .add_event::<AttackEvent>()
+            .add_event::<AnimationFinishedEvent>()
+            .add_event::<HurtEvent>()
+            .add_event::<ClipChangeEvent>()
+            .init_resource::<PlayedStates>()
+            .add_systems(
+                Update,
+                (track_action_phases, player_attack, advance_combos, collect_played_states).chain(),
+            );
+
+        let chain = player_combo();
+        let profile = chain.attacks[0].profile;
+        let player = app
+            .world_mut()
+            .spawn((Player, Facing::default(), chain, Combo::default(), profile))
+            .id();
+        app.update();
+
+        (app, player)
+    }
+
+    fn press_attack(app: &mut App) {
+        let now = app.world().resource::<Time>().elapsed();
+        app.world_mut().resource_mut::<InputBuffer>().push(ControlledAction::Attack, now);
+    }
+
+    #[derive(Resource, Default)]
+    struct PlayedStates(Vec<AnimationState>);
+
+    fn collect_played_states(mut reader: EventReader<ClipChangeEvent>, mut played: ResMut<PlayedStates>) {
+        played.0.extend(reader.read().map(|event| event.new_state));
+    }
+
+    fn played_states(app: &mut App) -> Vec<AnimationState> {
+        std::mem::take(&mut app.world_mut().resource_mut::<PlayedStates>().0)
+    }
+
+    fn run_for(app: &mut App, duration: Duration) {
+        for _ in 0..duration.as_millis().div_ceil(FRAME.as_millis()) {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn attack_pressed_before_the_clip_ends_chains() {
+        let (mut app, player) = combo_app();
+
+        press_attack(&mut app);
+        app.update();
+        assert_eq!(played_states(&mut app), vec![AnimationState::Attack]);
+
+        run_for(&mut app, Duration::from_millis(100));
+        press_attack(&mut app);
+        run_for(&mut app, Duration::from_millis(300));
+
+        app.world_mut()
+            .send_event(AnimationFinishedEvent::new(&player, AnimationState::Attack, Direction::Zero));
+        run_for(&mut app, Duration::from_millis(200));
+
+        assert_eq!(played_states(&mut app), vec![AnimationState::Attack2]);
+        assert_eq!(app.world().get::<Combo>(player).unwrap().step, Some(1));
+    }
+}
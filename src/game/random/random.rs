@@ -0,0 +1,12 @@
+use bevy::prelude::{Deref, DerefMut, Resource};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
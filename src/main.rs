@@ -28,7 +28,7 @@ fn main() {
         .add_systems(
             Update,
             (
-                exit_on_escape,
+                exit_on_quit,
                 transition_to_game_state,
                 transition_to_main_menu_state,
             ),
@@ -1,13 +1,14 @@
 use bevy::prelude::*;
 
+use crate::game::controls::context::{ContextAction, ContextActionEvent};
 use crate::game::game::GameState;
 use crate::AppState;
 
-pub fn exit_on_escape(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+pub fn exit_on_quit(
+    mut context_events: EventReader<ContextActionEvent>,
     mut app_exit_events: ResMut<Events<AppExit>>,
 ) {
-    if keyboard_input.pressed(KeyCode::Escape) {
+    if context_events.read().any(|event| event.action == ContextAction::Quit) {
         app_exit_events.send(AppExit::Success);
     }
 }
@@ -21,10 +22,10 @@ pub fn play_background_sound(mut commands: Commands, asset_server: Res<AssetServ
 
 pub fn transition_to_game_state(
     app_state: Res<State<AppState>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut context_events: EventReader<ContextActionEvent>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyG) {
+    if context_events.read().any(|event| event.action == ContextAction::StartGame) {
         if *app_state.get() != AppState::Game {
             next_state.set(AppState::Game);
             info!("Game started")
@@ -34,11 +35,11 @@ pub fn transition_to_game_state(
 
 pub fn transition_to_main_menu_state(
     app_state: Res<State<AppState>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut context_events: EventReader<ContextActionEvent>,
     mut next_app_state: ResMut<NextState<AppState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyM) {
+    if context_events.read().any(|event| event.action == ContextAction::OpenMainMenu) {
         if *app_state.get() != AppState::MainMenu {
             next_app_state.set(AppState::MainMenu);
             next_game_state.set(GameState::Paused);
@@ -0,0 +1,17 @@
+use bevy::app::App;
+use bevy::prelude::{OnEnter, OnExit, Plugin};
+use crate::AppState;
+use crate::ui::game_over::systems::layout::{despawn_game_over_screen, spawn_game_over_screen};
+
+pub mod components;
+mod systems;
+
+pub struct GameOverUiPlugin;
+
+impl Plugin for GameOverUiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::GameOver), spawn_game_over_screen)
+            .add_systems(OnExit(AppState::GameOver), despawn_game_over_screen);
+    }
+}
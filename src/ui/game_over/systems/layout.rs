@@ -0,0 +1,64 @@
+use bevy::asset::AssetServer;
+use bevy::color::Color;
+use bevy::prelude::{
+    default, AlignItems, BuildChildren, Commands, DespawnRecursiveExt, Entity, FlexDirection,
+    ImageBundle, JustifyContent, Query, Res, Style, TextBundle, TextStyle, UiImage, Val, With,
+};
+
+use crate::game::controls::bindings::KeyBindings;
+use crate::game::controls::context::{ContextAction, InputContext};
+use crate::ui::game_over::components::GameOverScreen;
+
+pub fn spawn_game_over_screen(mut commands: Commands, asset_server: Res<AssetServer>, key_bindings: Res<KeyBindings>) {
+    build_game_over_screen(&mut commands, &asset_server, &key_bindings);
+}
+
+pub fn despawn_game_over_screen(mut commands: Commands, query: Query<Entity, With<GameOverScreen>>) {
+    if let Ok(entity) = query.get_single() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn build_game_over_screen(commands: &mut Commands, asset_server: &Res<AssetServer>, key_bindings: &KeyBindings) -> Entity {
+    let context_key = |action| {
+        key_bindings
+            .contexts
+            .keys_for(InputContext::Menu, action)
+            .first()
+            .map(|key| format!("{:?}", key))
+            .unwrap_or_default()
+    };
+    let hint = format!(
+        "New Game - '{}' Quit - '{}'",
+        context_key(ContextAction::StartGame),
+        context_key(ContextAction::Quit),
+    );
+
+    commands
+        .spawn((
+            ImageBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::FlexEnd,
+                    align_items: AlignItems::Center,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                image: UiImage::new(asset_server.load("images/menu/you_lose.png")),
+                ..default()
+            },
+            GameOverScreen {},
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                hint,
+                TextStyle {
+                    font: asset_server.load("fonts/Paint-Peel-Cyr.ttf"),
+                    font_size: 45.0,
+                    color: Color::WHITE,
+                },
+            ));
+        })
+        .id()
+}
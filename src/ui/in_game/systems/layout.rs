@@ -2,10 +2,13 @@ use bevy::asset::AssetServer;
 use bevy::color::Color;
 use bevy::prelude::{Commands, default, DespawnRecursiveExt, Entity, Query, Res, Text, TextBundle, TextSection, TextStyle, With};
 
+use crate::game::controls::bindings::KeyBindings;
+use crate::game::controls::context::{ContextAction, InputContext};
+use crate::game::controls::controls::ControlledAction;
 use crate::ui::in_game::components::ControlsHint;
 
-pub fn spawn_controls_hint(mut commands: Commands, asset_server: Res<AssetServer>) {
-    build_main_menu(&mut commands, &asset_server);
+pub fn spawn_controls_hint(mut commands: Commands, asset_server: Res<AssetServer>, key_bindings: Res<KeyBindings>) {
+    build_main_menu(&mut commands, &asset_server, &key_bindings);
 }
 
 pub fn despwan_controls_hint(mut commands: Commands, query: Query<Entity, With<ControlsHint>>) {
@@ -14,13 +17,37 @@ pub fn despwan_controls_hint(mut commands: Commands, query: Query<Entity, With<C
     }
 }
 
-pub fn build_main_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+pub fn build_main_menu(commands: &mut Commands, asset_server: &Res<AssetServer>, key_bindings: &KeyBindings) -> Entity {
+    let key = |action| {
+        key_bindings
+            .primary_key(action)
+            .map(|key| format!("{:?}", key))
+            .unwrap_or_default()
+    };
+    let context_key = |context, action| {
+        key_bindings
+            .contexts
+            .keys_for(context, action)
+            .first()
+            .map(|key| format!("{:?}", key))
+            .unwrap_or_default()
+    };
+    let hint = format!(
+        "Pause - '{}' Attack - '{}' Dash - '{}' Block - '{}' Main Menu (paused) - '{}' Accelerate - '{}'",
+        context_key(InputContext::Gameplay, ContextAction::TogglePause),
+        key(ControlledAction::Attack),
+        key(ControlledAction::Dash),
+        key(ControlledAction::Block),
+        context_key(InputContext::Paused, ContextAction::OpenMainMenu),
+        key(ControlledAction::Run),
+    );
+
     return commands
         .spawn((
             TextBundle {
                 text: Text {
                     sections: vec![TextSection::new(
-                        "Pause - 'SPACE' Attack - 'F' Main Menu - 'M' Accelerate - 'Shift'",
+                        hint,
                         TextStyle {
                             font: asset_server.load("fonts/Paint-Peel-Cyr.ttf"),
                             font_size: 45.0,
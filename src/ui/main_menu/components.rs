@@ -1,5 +1,7 @@
 use bevy::prelude::Component;
 
+use crate::game::controls::controls::ControlledAction;
+
 #[derive(Component)]
 pub struct MainMenu;
 
@@ -20,3 +22,24 @@ pub struct SettingsButton;
 
 #[derive(Component)]
 pub struct Dimmed;
+
+#[derive(Component)]
+pub struct ControlsButton;
+
+#[derive(Component)]
+pub struct ControlsMenu;
+
+#[derive(Component)]
+pub struct BindingButton(pub ControlledAction);
+
+#[derive(Component)]
+pub struct BindingLabel(pub ControlledAction);
+
+#[derive(Component)]
+pub struct BindingStatus;
+
+#[derive(Component)]
+pub struct ResetBindingsButton;
+
+#[derive(Component)]
+pub struct BackButton;
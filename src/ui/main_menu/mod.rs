@@ -1,10 +1,17 @@
 use bevy::prelude::{in_state, App, IntoSystemConfigs, OnEnter, OnExit, Plugin, Update};
 
+use crate::game::controls::context::emit_context_actions;
 use crate::AppState;
-use systems::interactions::interact_with_play_button;
-use systems::layout::{despwan_main_menu, spawn_main_menu};
+use resources::Rebinding;
+use systems::interactions::{
+    capture_rebinding_key, interact_with_back_button, interact_with_binding_buttons,
+    interact_with_controls_button, interact_with_play_button, interact_with_reset_bindings_button,
+    update_binding_labels,
+};
+use systems::layout::{despawn_controls_menu, despwan_main_menu, spawn_main_menu};
 
 pub mod components;
+pub mod resources;
 mod styles;
 mod systems;
 
@@ -12,11 +19,21 @@ pub struct MainMenuPlugin;
 
 impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
-            .add_systems(OnExit(AppState::MainMenu), despwan_main_menu)
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(AppState::MainMenu), (despwan_main_menu, despawn_controls_menu))
             .add_systems(
                 Update,
-                interact_with_play_button.run_if(in_state(AppState::MainMenu)),
+                (
+                    interact_with_play_button,
+                    interact_with_controls_button,
+                    interact_with_back_button,
+                    interact_with_binding_buttons,
+                    interact_with_reset_bindings_button,
+                    capture_rebinding_key.before(emit_context_actions),
+                    update_binding_labels,
+                )
+                    .run_if(in_state(AppState::MainMenu)),
             );
     }
 }
@@ -0,0 +1,9 @@
+use bevy::prelude::Resource;
+
+use crate::game::controls::controls::ControlledAction;
+
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding {
+    pub action: Option<ControlledAction>,
+    pub status: String,
+}
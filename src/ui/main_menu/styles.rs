@@ -30,6 +30,16 @@ pub fn get_button_style() -> Style {
     }
 }
 
+pub fn get_binding_button_style() -> Style {
+    Style {
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        width: Val::Px(640.0),
+        height: Val::Px(56.0),
+        ..Style::DEFAULT
+    }
+}
+
 pub fn get_main_menu_image_style() -> Style {
     Style {
         width: Val::Percent(100.0),
@@ -74,3 +84,11 @@ pub fn get_button_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
         color: Color::BLACK,
     }
 }
+
+pub fn get_binding_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("fonts/Paint-Peel-Cyr.ttf"),
+        font_size: 36.0,
+        color: Color::BLACK,
+    }
+}
@@ -1,22 +1,37 @@
 use bevy::color::Color;
-use bevy::prelude::{BackgroundColor, Changed, Interaction, NextState, Query, ResMut, With};
+use bevy::prelude::{
+    info, warn, AssetServer, BackgroundColor, ButtonInput, Changed, Commands, DespawnRecursiveExt,
+    Entity, EventWriter, Interaction, KeyCode, Query, Res, ResMut, Text, With, Without,
+};
 
-use crate::ui::main_menu::components::PlayButton;
+use crate::game::controls::bindings::KeyBindings;
+use crate::game::controls::context::{ContextAction, ContextActionEvent, InputContext};
+use crate::ui::main_menu::components::{
+    BackButton, BindingButton, BindingLabel, BindingStatus, ControlsButton, ControlsMenu, MainMenu,
+    PlayButton, ResetBindingsButton,
+};
+use crate::ui::main_menu::resources::Rebinding;
 use crate::ui::main_menu::styles::{HOVERED_BUTTON_COLOR, PRESSED_BUTTON_COLOR};
-use crate::AppState;
+use crate::ui::main_menu::systems::layout::{build_controls_menu, build_main_menu};
+
+type ButtonQuery<'w, 's, T> =
+    Query<'w, 's, (&'static Interaction, &'static mut BackgroundColor), (Changed<Interaction>, With<T>)>;
 
 pub fn interact_with_play_button(
     mut button_query: Query<
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<PlayButton>),
     >,
-    mut app_state_next_state: ResMut<NextState<AppState>>,
+    mut context_event_writer: EventWriter<ContextActionEvent>,
 ) {
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *background_color = PRESSED_BUTTON_COLOR.into();
-                app_state_next_state.set(AppState::Game);
+                context_event_writer.send(ContextActionEvent {
+                    context: InputContext::Menu,
+                    action: ContextAction::StartGame,
+                });
             }
             Interaction::Hovered => {
                 *background_color = HOVERED_BUTTON_COLOR.into();
@@ -27,3 +42,145 @@ pub fn interact_with_play_button(
         }
     }
 }
+
+pub fn interact_with_controls_button(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut button_query: ButtonQuery<ControlsButton>,
+    main_menu_query: Query<Entity, With<MainMenu>>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        *background_color = button_color(interaction);
+
+        if *interaction == Interaction::Pressed {
+            if let Ok(main_menu_entity) = main_menu_query.get_single() {
+                commands.entity(main_menu_entity).despawn_recursive();
+            }
+            build_controls_menu(&mut commands, &asset_server);
+        }
+    }
+}
+
+pub fn interact_with_back_button(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut button_query: ButtonQuery<BackButton>,
+    controls_menu_query: Query<Entity, With<ControlsMenu>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        *background_color = button_color(interaction);
+
+        if *interaction == Interaction::Pressed {
+            if let Ok(controls_menu_entity) = controls_menu_query.get_single() {
+                commands.entity(controls_menu_entity).despawn_recursive();
+            }
+            *rebinding = Rebinding::default();
+            build_main_menu(&mut commands, &asset_server);
+        }
+    }
+}
+
+pub fn interact_with_binding_buttons(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor, &BindingButton),
+        Changed<Interaction>,
+    >,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (interaction, mut background_color, binding_button) in button_query.iter_mut() {
+        *background_color = button_color(interaction);
+
+        if *interaction == Interaction::Pressed {
+            if rebinding.action == Some(binding_button.0) {
+                *rebinding = Rebinding::default();
+            } else {
+                rebinding.action = Some(binding_button.0);
+                rebinding.status = format!("Press a key for {:?}", binding_button.0);
+            }
+        }
+    }
+}
+
+pub fn interact_with_reset_bindings_button(
+    mut button_query: ButtonQuery<ResetBindingsButton>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        *background_color = button_color(interaction);
+
+        if *interaction == Interaction::Pressed {
+            *key_bindings = KeyBindings::default();
+            save_key_bindings(&key_bindings);
+            *rebinding = Rebinding {
+                action: None,
+                status: "Bindings reset to defaults".to_string(),
+            };
+        }
+    }
+}
+
+pub fn capture_rebinding_key(
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+
+    let Some(key) = keyboard_input.get_just_pressed().next().copied() else {
+        return;
+    };
+
+    keyboard_input.clear_just_pressed(key);
+
+    match key_bindings.rebind(action, key) {
+        Ok(()) => {
+            info!("Bound {:?} to {:?}", action, key);
+            save_key_bindings(&key_bindings);
+            *rebinding = Rebinding {
+                action: None,
+                status: format!("{:?} bound to {:?}", action, key),
+            };
+        }
+        Err(conflict) => {
+            rebinding.status = format!("{}, press another key for {:?}", conflict, action);
+        }
+    }
+}
+
+pub fn update_binding_labels(
+    key_bindings: Res<KeyBindings>,
+    rebinding: Res<Rebinding>,
+    mut label_query: Query<(&mut Text, &BindingLabel)>,
+    mut status_query: Query<&mut Text, (With<BindingStatus>, Without<BindingLabel>)>,
+) {
+    for (mut text, label) in label_query.iter_mut() {
+        text.sections[0].value = if rebinding.action == Some(label.0) {
+            format!("{:?}: ...", label.0)
+        } else {
+            let keys: Vec<String> = key_bindings.keys_for(label.0).iter().map(|key| format!("{:?}", key)).collect();
+            format!("{:?}: {}", label.0, keys.join(" / "))
+        };
+    }
+
+    if let Ok(mut text) = status_query.get_single_mut() {
+        text.sections[0].value.clone_from(&rebinding.status);
+    }
+}
+
+fn save_key_bindings(key_bindings: &KeyBindings) {
+    if let Err(error) = key_bindings.save() {
+        warn!("Could not save key bindings: {}", error);
+    }
+}
+
+fn button_color(interaction: &Interaction) -> BackgroundColor {
+    match *interaction {
+        Interaction::Pressed => PRESSED_BUTTON_COLOR.into(),
+        Interaction::Hovered => HOVERED_BUTTON_COLOR.into(),
+        Interaction::None => BackgroundColor(Color::NONE),
+    }
+}
@@ -1,11 +1,19 @@
 use bevy::prelude::{
-    default, AssetServer, BackgroundColor, BuildChildren, ButtonBundle, Color, Commands,
-    DespawnRecursiveExt, Entity, ImageBundle, Query, Res, Text, TextBundle, TextSection, UiImage,
-    With,
+    default, AssetServer, BackgroundColor, BuildChildren, ButtonBundle, ChildBuilder, Color, Commands,
+    Component, DespawnRecursiveExt, Entity, ImageBundle, Query, Res, ResMut, Style, Text, TextBundle,
+    TextSection, TextStyle, UiImage, With,
 };
 
-use crate::ui::main_menu::components::{MainMenu, PlayButton};
-use crate::ui::main_menu::styles::{get_button_style, get_button_text_style, get_main_menu_style};
+use crate::game::controls::controls::ControlledAction;
+use crate::ui::main_menu::components::{
+    BackButton, BindingButton, BindingLabel, BindingStatus, ControlsButton, ControlsMenu, MainMenu,
+    PlayButton, ResetBindingsButton,
+};
+use crate::ui::main_menu::resources::Rebinding;
+use crate::ui::main_menu::styles::{
+    get_binding_button_style, get_binding_text_style, get_button_style, get_button_text_style,
+    get_main_menu_style,
+};
 
 pub fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
     build_main_menu(&mut commands, &asset_server);
@@ -49,6 +57,74 @@ pub fn build_main_menu(commands: &mut Commands, asset_server: &Res<AssetServer>)
                         ..default()
                     });
                 });
+
+            spawn_button(parent, ControlsButton {}, get_button_style(), "CONTROLS", get_button_text_style(asset_server));
         })
         .id();
 }
+
+pub fn despawn_controls_menu(
+    mut commands: Commands,
+    controls_menu_query: Query<Entity, With<ControlsMenu>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    if let Ok(controls_menu_entity) = controls_menu_query.get_single() {
+        commands.entity(controls_menu_entity).despawn_recursive();
+    }
+    *rebinding = Rebinding::default();
+}
+
+pub fn build_controls_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands
+        .spawn((
+            ImageBundle {
+                style: get_main_menu_style(),
+                image: UiImage::new(asset_server.load("images/menu/main_menu.png")),
+                ..default()
+            },
+            ControlsMenu {},
+        ))
+        .with_children(|parent| {
+            for action in ControlledAction::BINDABLE {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: get_binding_button_style(),
+                            background_color: BackgroundColor(Color::NONE),
+                            ..default()
+                        },
+                        BindingButton(action),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section("", get_binding_text_style(asset_server)),
+                            BindingLabel(action),
+                        ));
+                    });
+            }
+
+            parent.spawn((
+                TextBundle::from_section("", get_binding_text_style(asset_server)),
+                BindingStatus {},
+            ));
+
+            spawn_button(parent, ResetBindingsButton {}, get_binding_button_style(), "RESET TO DEFAULTS", get_binding_text_style(asset_server));
+            spawn_button(parent, BackButton {}, get_button_style(), "BACK", get_button_text_style(asset_server));
+        })
+        .id()
+}
+
+fn spawn_button(parent: &mut ChildBuilder, marker: impl Component, style: Style, label: &str, text_style: TextStyle) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style,
+                background_color: BackgroundColor(Color::NONE),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style));
+        });
+}
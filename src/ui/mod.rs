@@ -2,7 +2,9 @@ use crate::ui::main_menu::MainMenuPlugin;
 use bevy::app::App;
 use bevy::prelude::Plugin;
 use crate::ui::in_game::InGameUiPlugin;
+use crate::ui::game_over::GameOverUiPlugin;
 
+pub mod game_over;
 pub mod in_game;
 pub mod main_menu;
 
@@ -10,6 +12,6 @@ pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((MainMenuPlugin, InGameUiPlugin));
+        app.add_plugins((MainMenuPlugin, InGameUiPlugin, GameOverUiPlugin));
     }
 }